@@ -1,17 +1,24 @@
-use crate::packages::models::{Package, ShowMode, SortKey}; 
-use std::collections::{BTreeSet, HashMap};
+use crate::packages::{
+    deps::DependencyGraph,
+    models::{Package, ShowMode, SortKey},
+    orphans::Orphans,
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum FilterState {
     Include,
     Exclude,
+    #[default]
     Ignore,
 }
-
-impl Default for FilterState {
-    fn default() -> Self {
-        FilterState::Ignore
-    }
+/// Restricts the package list to the dependency tree of a single package.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DependencyFilter {
+    /// Everything the package pulls in
+    DependenciesOf(String),
+    /// Everything that needs the package
+    RequiredBy(String),
 }
 
 // Filter packages based on criteria
@@ -20,8 +27,19 @@ pub fn filter_packages(
     tag_filters: &HashMap<String, FilterState>,
     repo_filters: &HashMap<String, FilterState>,
     show_mode: ShowMode, 
-    orphan_names: &[String], 
+    orphans: &Orphans,
+    dependency_filter: Option<&DependencyFilter>,
+    dependency_graph: &DependencyGraph,
 ) -> Vec<Package> {
+    let dependency_names = dependency_filter.map(|filter| match filter {
+        DependencyFilter::DependenciesOf(name) => dependency_graph.all_dependencies(name),
+        DependencyFilter::RequiredBy(name) => dependency_graph.all_dependents(name),
+    });
+    let explicit_names: HashSet<&str> = packages
+        .iter()
+        .filter(|p| p.is_explicit)
+        .map(|p| p.name.as_str())
+        .collect();
     let include_tags: Vec<_> = tag_filters
         .iter()
         .filter(|(_, v)| **v == FilterState::Include)
@@ -57,28 +75,47 @@ pub fn filter_packages(
             if include_repos.is_empty() {
                 true
             } else {
-                include_repos
-                    .iter()
-                    .any(|r| format!("{:?}", p.repository).to_lowercase() == r.to_lowercase())
+                let repo = p.repository.to_string();
+                include_repos.iter().any(|r| **r == repo)
             }
         })
         .filter(|p| {
-            !exclude_repos
-                .iter()
-                .any(|r| format!("{:?}", p.repository).to_lowercase() == r.to_lowercase())
+            let repo = p.repository.to_string();
+            !exclude_repos.iter().any(|r| **r == repo)
+        })
+        .filter(|p| {
+            dependency_names
+                .as_ref()
+                .is_none_or(|names| names.contains(&p.name))
         })
         .filter(|p| match show_mode {
             ShowMode::AllInstalled => true,
             ShowMode::ExplicitlyInstalled => p.is_explicit,
             ShowMode::Dependencies => !p.is_explicit,
-            ShowMode::Orphans => orphan_names.contains(&p.name),
+            ShowMode::Orphans => orphans.contains(&p.name),
+            ShowMode::Leaves => p.is_explicit && dependency_graph.required_by(&p.name).is_empty(),
+            ShowMode::RedundantExplicit => {
+                p.is_explicit
+                    && dependency_graph
+                        .required_by(&p.name)
+                        .iter()
+                        .any(|r| explicit_names.contains(r.as_str()))
+            }
             ShowMode::AllAvailable => true,
+            ShowMode::AurNeedsAttention => p.aur.as_ref().is_some_and(|aur| aur.needs_attention()),
+            ShowMode::AurUpdates => p.aur_update().is_some(),
+            ShowMode::RepoUpgrades => p.repo_update().is_some(),
+            ShowMode::TimeTravel => true,
+            ShowMode::PackageCache => true,
+            ShowMode::ConfigDrift => true,
+            ShowMode::ModifiedConfigs => true,
+            ShowMode::IntegrityProblems => p.has_integrity_problems(),
         })
         .cloned()
         .collect()
 }
 // Sort packages by a given key
-pub fn sort_packages(packages: &mut Vec<Package>, sort_key: SortKey) {
+pub fn sort_packages(packages: &mut [Package], sort_key: SortKey) {
     packages.sort_by(|a, b| match sort_key {
         SortKey::Name => a.name.cmp(&b.name),
         SortKey::Size => b
@@ -88,17 +125,73 @@ pub fn sort_packages(packages: &mut Vec<Package>, sort_key: SortKey) {
         SortKey::InstallDate => b.install_date.cmp(&a.install_date),
         SortKey::UpdateDate => b.build_date.cmp(&a.build_date),
         SortKey::Popularity => {
-            let a_pop = a.popularity.unwrap_or(0.0);
-            let b_pop = b.popularity.unwrap_or(0.0);
+            let a_pop = a.aur.as_ref().map_or(0.0, |aur| aur.popularity);
+            let b_pop = b.aur.as_ref().map_or(0.0, |aur| aur.popularity);
             b_pop.partial_cmp(&a_pop).unwrap_or(std::cmp::Ordering::Equal)
         }
+        SortKey::Votes => {
+            let votes = |p: &Package| p.aur.as_ref().map(|aur| aur.num_votes);
+            votes(b).cmp(&votes(a))
+        }
+        SortKey::LastModified => {
+            let modified = |p: &Package| p.aur.as_ref().map(|aur| aur.last_modified);
+            modified(b).cmp(&modified(a))
+        }
     });
 }
 
-pub fn get_all_repos(packages: &[Package]) -> Vec<String> {
+pub fn get_all_repos<'a>(packages: impl IntoIterator<Item = &'a Package>) -> Vec<String> {
     let mut repos: BTreeSet<String> = BTreeSet::new();
     for pkg in packages {
-        repos.insert(format!("{:?}", pkg.repository));
+        repos.insert(pkg.repository.to_string());
     }
     repos.into_iter().collect()
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::fixture::tests::example_packages;
+
+    async fn shown_names(show_mode: ShowMode, dependency_filter: Option<&DependencyFilter>) -> Vec<String> {
+        let packages = example_packages().await;
+        let graph = DependencyGraph::build(&packages);
+        let orphans = Orphans::find(&packages, &graph);
+        let mut names: Vec<String> = filter_packages(
+            &packages,
+            &HashMap::new(),
+            &HashMap::new(),
+            show_mode,
+            &orphans,
+            dependency_filter,
+            &graph,
+        )
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn leaves_are_explicit_packages_nothing_requires() {
+        // bash is explicit but needed by company-tools and, as sh, by firefox
+        assert_eq!(
+            shown_names(ShowMode::Leaves, None).await,
+            ["company-tools", "firefox", "ttf-legacy-fonts", "yay"]
+        );
+    }
+
+    #[tokio::test]
+    async fn redundant_explicit_packages_are_required_by_explicit_ones() {
+        assert_eq!(shown_names(ShowMode::RedundantExplicit, None).await, ["bash"]);
+    }
+
+    #[tokio::test]
+    async fn dependency_filter_limits_the_show_mode() {
+        let filter = DependencyFilter::RequiredBy("bash".to_string());
+        assert_eq!(shown_names(ShowMode::Leaves, Some(&filter)).await, ["company-tools", "firefox"]);
+
+        let filter = DependencyFilter::DependenciesOf("firefox".to_string());
+        assert_eq!(shown_names(ShowMode::RedundantExplicit, Some(&filter)).await, ["bash"]);
+    }
+}
@@ -23,11 +23,20 @@ pub enum ActionType {
         show_mode_whitelist: Vec<String>,
         #[serde(default)]
         show_mode_blacklist: Vec<String>,
+        /// Shows what removing the packages the command targets would take
+        /// with it and asks for confirmation before running the command.
+        /// Only applies to commands removing packages (`-R`).
+        #[serde(default = "default_preview_removal")]
+        preview_removal: bool,
     },
     /// Triggers an internal application action
     Local,
 }
 
+fn default_preview_removal() -> bool {
+    true
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Action {
     pub name: String,
@@ -47,10 +56,98 @@ impl Action {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PacmanConfig {
+    /// Installation root, equivalent to `pacman --root`
+    pub root: PathBuf,
+    /// Database directory, equivalent to `pacman --dbpath`
+    pub db_path: PathBuf,
+    /// pacman configuration file, equivalent to `pacman --config`
+    pub config_file: PathBuf,
+    /// pacman log file, equivalent to `pacman --logfile`
+    pub log_file: PathBuf,
+    /// Command prefix used to run pacman as root for built-in actions
+    pub privilege_command: Vec<String>,
+    /// Tool merging a config file with its `.pacnew` or `.pacsave`, run as
+    /// root with both paths appended, like pacdiff's `DIFFPROG`
+    pub merge_tool: Vec<String>,
+}
+
+impl Default for PacmanConfig {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            db_path: PathBuf::from("/var/lib/pacman"),
+            config_file: PathBuf::from("/etc/pacman.conf"),
+            log_file: PathBuf::from("/var/log/pacman.log"),
+            privilege_command: vec!["sudo".to_string()],
+            merge_tool: vec!["vim".to_string(), "-d".to_string()],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AurConfig {
+    /// How long cached AUR metadata is used before it is fetched again
+    pub cache_ttl_hours: u64,
+    /// Base URL of the AUR web interface hosting the RPC
+    pub base_url: String,
+    /// Number of RPC requests allowed in flight at once
+    pub max_concurrent_requests: usize,
+    /// How often a failed RPC request is retried
+    pub max_retries: u32,
+}
+
+impl Default for AurConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_hours: 24,
+            base_url: "https://aur.archlinux.org".to_string(),
+            max_concurrent_requests: 4,
+            max_retries: 3,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct UnownedConfig {
+    /// Directories searched for files no installed package owns
+    pub roots: Vec<PathBuf>,
+    /// Paths skipped with everything below them, like caches regenerated by
+    /// pacman hooks
+    pub exclude: Vec<PathBuf>,
+}
+
+impl Default for UnownedConfig {
+    fn default() -> Self {
+        Self {
+            roots: ["/usr", "/etc", "/opt"].map(PathBuf::from).to_vec(),
+            exclude: [
+                "/etc/ca-certificates/extracted",
+                "/etc/ld.so.cache",
+                "/etc/machine-id",
+                "/etc/pacman.d/gnupg",
+                "/etc/ssl/certs",
+                "/usr/lib/locale/locale-archive",
+                "/usr/share/info/dir",
+                "/usr/share/mime",
+            ]
+            .map(PathBuf::from)
+            .to_vec(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct Config {
     pub actions: Vec<Action>,
+    pub pacman: PacmanConfig,
+    pub aur: AurConfig,
+    pub unowned: UnownedConfig,
 }
 
 impl Default for Config {
@@ -66,6 +163,7 @@ impl Default for Config {
                         requires_package: false,
                         show_mode_whitelist: vec![],
                         show_mode_blacklist: vec![],
+                        preview_removal: false,
                     },
                 },
                 Action {
@@ -76,6 +174,7 @@ impl Default for Config {
                         requires_package: false,
                         show_mode_whitelist: vec![],
                         show_mode_blacklist: vec![],
+                        preview_removal: false,
                     },
                 },
                 Action {
@@ -86,6 +185,7 @@ impl Default for Config {
                         requires_package: true,
                         show_mode_whitelist: vec!["All Available".to_string()],
                         show_mode_blacklist: vec![],
+                        preview_removal: false,
                     },
                 },
                 Action {
@@ -96,31 +196,31 @@ impl Default for Config {
                         requires_package: true,
                         show_mode_whitelist: vec![],
                         show_mode_blacklist: vec!["All Available".to_string()],
+                        preview_removal: true,
                     },
                 },
                 Action {
                     name: "Remove Orphan Packages".to_string(),
                     key: ConfigKey { key: 'o', shift: false },
                     action_type: ActionType::Command {
-                        command: vec![
-                            "sudo".to_string(),
-                            "sh".to_string(),
-                            "-c".to_string(),
-                            "pacman -Rns $(pacman -Qdtq)".to_string(),
-                        ],
+                        command: vec!["sudo".to_string(), "pacman".to_string(), "-Rns".to_string(), "{orphans}".to_string()],
                         requires_package: false,
                         show_mode_whitelist: vec![],
                         show_mode_blacklist: vec![],
+                        preview_removal: true,
                     },
                 },
             ],
+            pacman: PacmanConfig::default(),
+            aur: AurConfig::default(),
+            unowned: UnownedConfig::default(),
         }
     }
 }
 
 // --- Config Loading ---
 
-fn get_config_dir() -> Result<PathBuf, AppError> {
+pub(crate) fn get_config_dir() -> Result<PathBuf, AppError> {
     let config_dir = dirs::config_dir()
         .ok_or_else(|| AppError::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "Config directory not found")))?
         .join("pacman_package_sorter");
@@ -167,20 +267,56 @@ pub fn load_config() -> Result<Config, AppError> { // CHANGED: Return type
 }
 
 /// Replaces placeholders in a command template with dynamic values.
+///
+/// `{package}` becomes the selected package, `{orphans}` one argument per
+/// orphan package.
 pub fn template_command(
     command_template: &[String],
     package_name: Option<&str>,
+    orphan_names: &[String],
 ) -> Result<Vec<String>, AppError> {
     let final_command = command_template
         .iter()
-        .map(|part| {
-            if part == "{package}" {
-                package_name.unwrap_or("{package}").to_string()
-            } else {
-                part.clone()
-            }
+        .flat_map(|part| match part.as_str() {
+            "{package}" => vec![package_name.unwrap_or("{package}").to_string()],
+            "{orphans}" => orphan_names.to_vec(),
+            _ => vec![part.clone()],
         })
         .collect::<Vec<String>>();
 
     Ok(final_command)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_removals_unless_disabled() {
+        let config: Config = toml::from_str(
+            r#"
+            [[actions]]
+            name = "Uninstall Package"
+            key = { key = "u" }
+            type = "Command"
+            command = ["sudo", "pacman", "-Rns", "{package}"]
+
+            [[actions]]
+            name = "Remove Quietly"
+            key = { key = "r" }
+            type = "Command"
+            command = ["sudo", "pacman", "-R", "{package}"]
+            preview_removal = false
+            "#,
+        )
+        .unwrap();
+        let previews: Vec<bool> = config
+            .actions
+            .iter()
+            .map(|action| match action.action_type {
+                ActionType::Command { preview_removal, .. } => preview_removal,
+                ActionType::Local => false,
+            })
+            .collect();
+        assert_eq!(previews, [true, false]);
+    }
+}
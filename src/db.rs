@@ -1,5 +1,4 @@
 use crate::error::AppError;
-use serde_json;
 use std::{
     collections::{BTreeSet, HashMap},
     fs,
@@ -7,7 +6,7 @@ use std::{
 };
 
 // Type alias for our tag database
-type TagDb = HashMap<String, Vec<String>>;
+pub type TagDb = HashMap<String, Vec<String>>;
 
 // Function to get the path to our tags.json file
 fn get_db_path() -> Result<PathBuf, AppError> {
@@ -6,9 +6,30 @@ mod packages;
 mod tui;
 
 use anyhow::Result;
+use clap::Parser;
+use packages::{fixture::FixtureSource, pacman::PacmanSource, source::PackageSource};
+use std::{path::PathBuf, sync::Arc};
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Read package data from a fixture directory instead of the live system
+    #[arg(long, value_name = "DIR")]
+    fixture: Option<PathBuf>,
+}
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<()> {
-    tui::run_tui().await?;
+    let cli = Cli::parse();
+
+    let source: Arc<dyn PackageSource> = match cli.fixture {
+        Some(dir) => Arc::new(FixtureSource::new(dir)),
+        None => {
+            let config = config::load_config().unwrap_or_default();
+            Arc::new(PacmanSource::new(config.pacman, config.aur))
+        }
+    };
+
+    tui::run_tui(source).await?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,31 @@
+use crate::error::AppError;
+use flate2::read::GzDecoder;
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+use xz2::read::XzDecoder;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const XZ_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Opens a tarball as written by makepkg or repo-add.
+///
+/// The compression is detected from the content rather than the file name,
+/// so gzip, zstd, xz and uncompressed archives are all accepted.
+pub fn open_archive(path: &Path) -> Result<tar::Archive<Box<dyn Read>>, AppError> {
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    let head = reader.fill_buf()?;
+    let reader: Box<dyn Read> = if head.starts_with(&GZIP_MAGIC) {
+        Box::new(GzDecoder::new(reader))
+    } else if head.starts_with(&ZSTD_MAGIC) {
+        Box::new(zstd::stream::read::Decoder::with_buffer(reader)?)
+    } else if head.starts_with(&XZ_MAGIC) {
+        Box::new(XzDecoder::new(reader))
+    } else {
+        Box::new(reader)
+    };
+    Ok(tar::Archive::new(reader))
+}
@@ -1,48 +1,301 @@
-use crate::error::AppError;
-use serde::Deserialize;
-use std::collections::HashMap;
+use crate::{config::AurConfig, error::AppError};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{sync::Semaphore, task::JoinSet};
 
-#[derive(Debug, Deserialize)]
+/// A package as described by the AUR RPC `info` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AurPackage {
     #[serde(rename = "Name")]
     pub name: String,
+    #[serde(rename = "PackageBase", default)]
+    pub package_base: Option<String>,
+    #[serde(rename = "Version", default)]
+    pub version: String,
+    #[serde(rename = "URL", default)]
+    pub url: Option<String>,
+    /// Snapshot download path, relative to the AUR base URL
+    #[serde(rename = "URLPath", default)]
+    pub url_path: Option<String>,
     #[serde(rename = "Popularity")]
     pub popularity: f64,
     #[serde(rename = "NumVotes")]
     pub num_votes: u32,
+    /// `None` when the package has no maintainer, i.e. is orphaned on the AUR
+    #[serde(rename = "Maintainer", default)]
+    pub maintainer: Option<String>,
+    /// Unix timestamp of when the package was flagged out of date
+    #[serde(rename = "OutOfDate", default)]
+    pub out_of_date: Option<i64>,
+    #[serde(rename = "FirstSubmitted", default)]
+    pub first_submitted: i64,
+    #[serde(rename = "LastModified", default)]
+    pub last_modified: i64,
+    #[serde(rename = "Keywords", default)]
+    pub keywords: Vec<String>,
+}
+
+impl AurPackage {
+    /// Orphaned or flagged out of date, i.e. worth a closer look
+    pub fn needs_attention(&self) -> bool {
+        self.maintainer.is_none() || self.out_of_date.is_some()
+    }
 }
 
 #[derive(Debug, Deserialize)]
-struct AurResponse {
-    results: Vec<AurPackage>,
-}
-
-// Fetch package data from AUR
-pub async fn fetch_aur_package_data(
-    package_names: Vec<String>,
-) -> Result<HashMap<String, AurPackage>, AppError> {
-    if package_names.is_empty() {
-        return Ok(HashMap::new());
-    }
-
-    let client = reqwest::Client::new();
-    let response: AurResponse = client
-        .get("https://aur.archlinux.org/rpc/v5/info")
-        .query(
-            &package_names
-                .into_iter()
-                .map(|name| ("arg[]", name))
-                .collect::<Vec<_>>(),
-        )
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    let mut aur_data = HashMap::new();
-    for pkg in response.results {
-        aur_data.insert(pkg.name.clone(), pkg);
-    }
-
-    Ok(aur_data)
-}
\ No newline at end of file
+pub(crate) struct AurResponse {
+    #[serde(default)]
+    pub(crate) results: Vec<AurPackage>,
+    /// Set when the RPC rejected the request
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+/// AUR metadata together with when it was fetched.
+#[derive(Debug, Clone)]
+pub struct AurInfo {
+    pub package: AurPackage,
+    pub fetched_at: DateTime<Utc>,
+    /// The entry is past its TTL but could not be refreshed
+    pub stale: bool,
+}
+
+/// Result of looking up packages on the AUR.
+#[derive(Debug, Default)]
+pub struct AurLookup {
+    /// Packages that exist on the AUR, keyed by name
+    pub packages: HashMap<String, AurInfo>,
+    /// Problems reaching the AUR or saving its cache; the packages are still usable
+    pub warnings: Vec<String>,
+}
+
+// The AUR rejects request URIs longer than 4443 bytes; stay below that
+const MAX_URL_LENGTH: usize = 4000;
+
+/// Packages fetched from the AUR, plus the names whose lookup failed.
+#[derive(Debug, Default)]
+pub struct AurFetch {
+    pub packages: HashMap<String, AurPackage>,
+    /// Package name to the error of the request it was part of
+    pub failed: HashMap<String, String>,
+}
+
+/// Client for the AUR RPC `info` endpoint.
+///
+/// Lookups are split into batches that fit the RPC's URL length limit, run
+/// with bounded concurrency and retried with exponential backoff.
+#[derive(Clone)]
+pub struct AurClient {
+    http: reqwest::Client,
+    base_url: String,
+    max_concurrent_requests: usize,
+    max_retries: u32,
+}
+
+impl AurClient {
+    pub fn from_config(config: &AurConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            max_concurrent_requests: config.max_concurrent_requests.max(1),
+            max_retries: config.max_retries,
+        }
+    }
+
+    fn info_url(&self) -> String {
+        format!("{}/rpc/v5/info", self.base_url)
+    }
+
+    /// Fetches the given packages; names not found on the AUR are simply absent.
+    pub async fn fetch(&self, package_names: Vec<String>) -> AurFetch {
+        let mut result = AurFetch::default();
+        if package_names.is_empty() {
+            return result;
+        }
+
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrent_requests));
+        let mut tasks = JoinSet::new();
+        // Batches by task, so the names of a task that panicked are known
+        let mut batches = HashMap::new();
+        for batch in batch_names(package_names, self.info_url().len()) {
+            let client = self.clone();
+            let semaphore = Arc::clone(&semaphore);
+            let names = batch.clone();
+            let task = tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                client.fetch_batch_with_retry(&batch).await
+            });
+            batches.insert(task.id(), names);
+        }
+
+        while let Some(joined) = tasks.join_next_with_id().await {
+            let (id, response) = match joined {
+                Ok((id, response)) => (id, response.map_err(|e| e.to_string())),
+                Err(e) => (e.id(), Err(format!("AUR lookup task failed: {}", e))),
+            };
+            let batch = batches.remove(&id).unwrap_or_default();
+            match response {
+                Ok(packages) => {
+                    for pkg in packages {
+                        result.packages.insert(pkg.name.clone(), pkg);
+                    }
+                }
+                // Failed names are retried on the next start instead of being
+                // cached as not on the AUR
+                Err(message) => {
+                    for name in batch {
+                        result.failed.insert(name, message.clone());
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    async fn fetch_batch_with_retry(&self, batch: &[String]) -> Result<Vec<AurPackage>, AppError> {
+        let mut attempt = 0;
+        loop {
+            match self.fetch_batch(batch).await {
+                Ok(packages) => return Ok(packages),
+                Err(e) if attempt < self.max_retries && is_retryable(&e) => {
+                    let delay = Duration::from_millis(500 * 2u64.pow(attempt));
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn fetch_batch(&self, batch: &[String]) -> Result<Vec<AurPackage>, AppError> {
+        let response: AurResponse = self
+            .http
+            .get(self.info_url())
+            .query(&batch.iter().map(|name| ("arg[]", name)).collect::<Vec<_>>())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        match response.error {
+            Some(error) => Err(AppError::CommandFailed(format!("AUR RPC error: {}", error))),
+            None => Ok(response.results),
+        }
+    }
+}
+
+// Network errors, rate limiting and server errors are worth another try
+fn is_retryable(error: &AppError) -> bool {
+    match error {
+        AppError::RequestError(e) => match e.status() {
+            Some(status) => status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+            None => !e.is_decode(),
+        },
+        _ => false,
+    }
+}
+
+// Splits names into batches whose `?arg[]=...&arg[]=...` query fits the URL limit
+fn batch_names(package_names: Vec<String>, base_length: usize) -> Vec<Vec<String>> {
+    // Every name is sent as `arg%5B%5D=<name>` plus a separator
+    const ARG_LENGTH: usize = "&arg%5B%5D=".len();
+
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    let mut length = base_length;
+    for name in package_names {
+        let name_length = ARG_LENGTH + encoded_length(&name);
+        if !batch.is_empty() && length + name_length > MAX_URL_LENGTH {
+            batches.push(std::mem::take(&mut batch));
+            length = base_length;
+        }
+        length += name_length;
+        batch.push(name);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
+// Length of a string after form URL encoding
+fn encoded_length(value: &str) -> usize {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'*' | b' ' => 1,
+            _ => 3,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(count: usize, length: usize) -> Vec<String> {
+        (0..count).map(|i| format!("{:0width$}", i, width = length)).collect()
+    }
+
+    #[test]
+    fn small_lookup_is_one_batch() {
+        let batches = batch_names(vec!["yay".to_string(), "paru".to_string()], 40);
+        assert_eq!(batches, [["yay", "paru"]]);
+        assert!(batch_names(Vec::new(), 40).is_empty());
+    }
+
+    #[test]
+    fn batches_stay_below_url_limit_and_keep_order() {
+        let package_names = names(500, 30);
+        let base_length = "https://aur.archlinux.org/rpc/v5/info".len();
+        let batches = batch_names(package_names.clone(), base_length);
+        assert!(batches.len() > 1);
+        for batch in &batches {
+            let length: usize = batch
+                .iter()
+                .map(|name| "&arg%5B%5D=".len() + encoded_length(name))
+                .sum();
+            assert!(base_length + length <= MAX_URL_LENGTH);
+        }
+        assert_eq!(batches.concat(), package_names);
+    }
+
+    #[test]
+    fn overlong_name_gets_its_own_batch() {
+        let long = "x".repeat(MAX_URL_LENGTH);
+        let batches = batch_names(vec!["a".to_string(), long.clone(), "b".to_string()], 40);
+        assert_eq!(batches, [vec!["a".to_string()], vec![long], vec!["b".to_string()]]);
+    }
+
+    #[test]
+    fn counts_percent_encoded_bytes() {
+        assert_eq!(encoded_length("python-foo_bar.1"), 16);
+        assert_eq!(encoded_length("c++"), 7);
+        assert_eq!(encoded_length("ü"), 6);
+    }
+
+    fn status_error(status: u16) -> AppError {
+        let response = http::Response::builder().status(status).body("").unwrap();
+        reqwest::Response::from(response)
+            .error_for_status()
+            .unwrap_err()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limits_and_server_errors_only() {
+        assert!(is_retryable(&status_error(429)));
+        assert!(is_retryable(&status_error(503)));
+        assert!(!is_retryable(&status_error(404)));
+
+        let decode_error = reqwest::Response::from(http::Response::new("not json"))
+            .json::<AurResponse>()
+            .await
+            .unwrap_err();
+        assert!(!is_retryable(&decode_error.into()));
+        assert!(!is_retryable(&AppError::CommandFailed("AUR RPC error".to_string())));
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    config,
+    error::AppError,
+    packages::aur::{AurInfo, AurPackage},
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::PathBuf};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    fetched_at: DateTime<Utc>,
+    /// `None` records that the package is not on the AUR, so purely
+    /// foreign packages are not looked up again on every start
+    package: Option<AurPackage>,
+}
+
+/// On-disk cache of AUR metadata, stored as `aur_cache.json` in the config directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AurCache {
+    entries: HashMap<String, CacheEntry>,
+}
+
+fn get_cache_path() -> Result<PathBuf, AppError> {
+    Ok(config::get_config_dir()?.join("aur_cache.json"))
+}
+
+impl AurCache {
+    /// Loads the cache, starting over with an empty one if it is missing or unreadable.
+    pub fn load() -> Self {
+        get_cache_path()
+            .and_then(|path| Ok(fs::read_to_string(path)?))
+            .and_then(|content| Ok(serde_json::from_str(&content)?))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        let content = serde_json::to_string(self)?;
+        fs::write(get_cache_path()?, content)?;
+        Ok(())
+    }
+
+    /// Names among `package_names` without a cache entry younger than `ttl`.
+    pub fn expired(&self, package_names: &[String], ttl: Duration, now: DateTime<Utc>) -> Vec<String> {
+        package_names
+            .iter()
+            .filter(|name| {
+                self.entries
+                    .get(*name)
+                    .is_none_or(|entry| now - entry.fetched_at >= ttl)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Records the result of fetching `requested` from the AUR.
+    ///
+    /// Requested names missing from `fetched` are stored as not being on the AUR.
+    pub fn update(
+        &mut self,
+        requested: &[String],
+        mut fetched: HashMap<String, AurPackage>,
+        now: DateTime<Utc>,
+    ) {
+        for name in requested {
+            let package = fetched.remove(name);
+            self.entries.insert(name.clone(), CacheEntry { fetched_at: now, package });
+        }
+    }
+
+    /// Cached AUR packages among `package_names`; entries older than `ttl` are marked stale.
+    pub fn lookup(
+        &self,
+        package_names: &[String],
+        ttl: Duration,
+        now: DateTime<Utc>,
+    ) -> HashMap<String, AurInfo> {
+        package_names
+            .iter()
+            .filter_map(|name| {
+                let entry = self.entries.get(name)?;
+                let package = entry.package.clone()?;
+                Some((
+                    name.clone(),
+                    AurInfo {
+                        package,
+                        fetched_at: entry.fetched_at,
+                        stale: now - entry.fetched_at >= ttl,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn package(name: &str) -> AurPackage {
+        serde_json::from_value(serde_json::json!({
+            "Name": name,
+            "Version": "1.0-1",
+            "Popularity": 0.5,
+            "NumVotes": 10,
+        }))
+        .unwrap()
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn expires_missing_and_old_entries() {
+        let fetched_at = Utc.with_ymd_and_hms(2024, 10, 1, 12, 0, 0).unwrap();
+        let ttl = Duration::hours(24);
+        let mut cache = AurCache::default();
+        cache.update(
+            &names(&["yay", "company-tools"]),
+            HashMap::from([("yay".to_string(), package("yay"))]),
+            fetched_at,
+        );
+
+        let requested = names(&["yay", "company-tools", "paru"]);
+        assert_eq!(
+            cache.expired(&requested, ttl, fetched_at + Duration::hours(23)),
+            ["paru"]
+        );
+        assert_eq!(cache.expired(&requested, ttl, fetched_at + ttl), requested);
+    }
+
+    #[test]
+    fn looks_up_aur_packages_and_marks_stale_ones() {
+        let fetched_at = Utc.with_ymd_and_hms(2024, 10, 1, 12, 0, 0).unwrap();
+        let ttl = Duration::hours(24);
+        let mut cache = AurCache::default();
+        cache.update(
+            &names(&["yay", "company-tools"]),
+            HashMap::from([("yay".to_string(), package("yay"))]),
+            fetched_at,
+        );
+        let requested = names(&["yay", "company-tools", "paru"]);
+
+        let fresh = cache.lookup(&requested, ttl, fetched_at + Duration::hours(1));
+        // Packages recorded as not on the AUR and uncached ones are absent
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh["yay"].package.version, "1.0-1");
+        assert_eq!(fresh["yay"].fetched_at, fetched_at);
+        assert!(!fresh["yay"].stale);
+
+        let stale = cache.lookup(&requested, ttl, fetched_at + Duration::days(2));
+        assert!(stale["yay"].stale);
+    }
+}
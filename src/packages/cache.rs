@@ -0,0 +1,259 @@
+use crate::{
+    error::AppError,
+    packages::{
+        models::Package,
+        pacman::bytes_to_mib,
+        vercmp::vercmp,
+    },
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+/// A package file in the pacman cache.
+#[derive(Debug, Clone)]
+pub struct CachedFile {
+    pub path: PathBuf,
+    pub version: String,
+    /// Size of the package file and its signature, in bytes
+    pub size: u64,
+    /// The detached `.sig` file next to the package, if present
+    pub signature: Option<PathBuf>,
+}
+
+/// The package files in pacman's cache directories, grouped by package.
+#[derive(Debug, Clone, Default)]
+pub struct PackageCache {
+    /// Package name -> cached files, newest version first
+    files: BTreeMap<String, Vec<CachedFile>>,
+}
+
+impl PackageCache {
+    /// Reads the given cache directories. Directories that do not exist and
+    /// entries that cannot be read, like dangling symlinks, are skipped, and a
+    /// file reachable through several directories (e.g. a directory listed
+    /// twice or a symlinked one) is only counted once.
+    pub fn scan(dirs: &[PathBuf]) -> Result<Self, AppError> {
+        let mut cache = Self::default();
+        let mut seen = HashSet::new();
+        for dir in dirs {
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Some((name, version)) = path
+                    .file_name()
+                    .and_then(|f| f.to_str())
+                    .and_then(parse_file_name)
+                else {
+                    continue;
+                };
+
+                let mut sig_path = path.clone().into_os_string();
+                sig_path.push(".sig");
+                let signature = PathBuf::from(sig_path);
+                let Ok(metadata) = fs::metadata(&path) else {
+                    continue;
+                };
+                if !seen.insert((metadata.dev(), metadata.ino())) {
+                    continue;
+                }
+                let signature_size = fs::metadata(&signature).ok().map(|m| m.len());
+                let size = metadata.len() + signature_size.unwrap_or_default();
+
+                cache.files.entry(name).or_default().push(CachedFile {
+                    path,
+                    version,
+                    size,
+                    signature: signature_size.map(|_| signature),
+                });
+            }
+        }
+        for files in cache.files.values_mut() {
+            files.sort_by(|a, b| vercmp(&b.version, &a.version));
+        }
+        Ok(cache)
+    }
+
+    /// Cached files of a package, newest version first.
+    pub fn versions(&self, name: &str) -> &[CachedFile] {
+        self.files.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.files.values().map(Vec::len).sum()
+    }
+
+    /// Total size of all cached files, in bytes
+    pub fn total_size(&self) -> u64 {
+        self.files.values().flatten().map(|f| f.size).sum()
+    }
+
+    /// Files a `paccache -rk <keep>` style cleanup would delete: everything
+    /// but the `keep` newest versions of each package. Copies of a version in
+    /// several cache directories count as one version. The installed version
+    /// is always kept, even if it is older.
+    pub fn cleanup_candidates(&self, keep: usize, installed: &[Package]) -> Vec<&CachedFile> {
+        let installed: HashMap<&str, &str> = installed
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str()))
+            .collect();
+        self.files
+            .iter()
+            .flat_map(|(name, files)| {
+                // Files are sorted by version, so copies are next to each other
+                let mut kept: Vec<&str> = files.iter().map(|f| f.version.as_str()).collect();
+                kept.dedup();
+                kept.truncate(keep);
+                kept.extend(installed.get(name.as_str()).copied());
+                files.iter().filter(move |f| !kept.contains(&f.version.as_str()))
+            })
+            .collect()
+    }
+
+    /// Forgets files that were deleted from disk.
+    pub fn remove(&mut self, paths: &[PathBuf]) {
+        for files in self.files.values_mut() {
+            files.retain(|f| !paths.contains(&f.path));
+        }
+        self.files.retain(|_, files| !files.is_empty());
+    }
+
+    /// One entry per cached package for the package list, with the details
+    /// of the installed or available package where known. `size` holds the
+    /// space the package takes up in the cache.
+    pub fn packages(&self, installed: &[Package], available: &[Package]) -> Vec<Package> {
+        let installed: HashMap<&str, &Package> =
+            installed.iter().map(|p| (p.name.as_str(), p)).collect();
+        let mut available_by_name: HashMap<&str, &Package> = HashMap::new();
+        for pkg in available {
+            available_by_name.entry(pkg.name.as_str()).or_insert(pkg);
+        }
+
+        self.files
+            .iter()
+            .map(|(name, files)| {
+                let mut package = match installed.get(name.as_str()) {
+                    Some(&pkg) => pkg.clone(),
+                    None => {
+                        let mut pkg = available_by_name
+                            .get(name.as_str())
+                            .map_or_else(|| Package::unknown(name), |&p| p.clone());
+                        pkg.version = files[0].version.clone();
+                        pkg
+                    }
+                };
+                package.size = bytes_to_mib(files.iter().map(|f| f.size).sum());
+                package
+            })
+            .collect()
+    }
+}
+
+impl CachedFile {
+    /// The package file and its signature, as deleted by a cleanup
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(self.path.as_path()).chain(self.signature.as_deref())
+    }
+}
+
+// Splits `name-pkgver-pkgrel-arch.pkg.tar.<ext>` into name and version.
+// Signatures and partial downloads are not package files.
+fn parse_file_name(file_name: &str) -> Option<(String, String)> {
+    if file_name.ends_with(".sig") || file_name.ends_with(".part") {
+        return None;
+    }
+    let stem = &file_name[..file_name.find(".pkg.tar")?];
+    let mut parts = stem.rsplitn(4, '-');
+    let _arch = parts.next()?;
+    let pkgrel = parts.next()?;
+    let pkgver = parts.next()?;
+    let name = parts.next()?;
+    Some((name.to_string(), format!("{}-{}", pkgver, pkgrel)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_cache() -> PathBuf {
+        PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/example/cache"))
+    }
+
+    fn cached(dir: &str, name: &str, version: &str, size: u64) -> CachedFile {
+        CachedFile {
+            path: PathBuf::from(format!("{}/{}-{}-x86_64.pkg.tar.zst", dir, name, version)),
+            version: version.to_string(),
+            size,
+            signature: None,
+        }
+    }
+
+    fn installed(name: &str, version: &str) -> Package {
+        let mut pkg = Package::unknown(name);
+        pkg.version = version.to_string();
+        pkg
+    }
+
+    #[test]
+    fn scans_each_file_once() {
+        let once = PackageCache::scan(&[example_cache()]).unwrap();
+        let twice = PackageCache::scan(&[example_cache(), example_cache()]).unwrap();
+        assert_eq!(twice.file_count(), once.file_count());
+        assert_eq!(twice.total_size(), once.total_size());
+
+        let firefox: Vec<&str> = once.versions("firefox").iter().map(|f| f.version.as_str()).collect();
+        assert_eq!(firefox, ["131.0-1", "130.0.1-1", "129.0-2", "128.0.3-1"]);
+        // Partial downloads are not package files
+        assert!(once.versions("libfoo").is_empty());
+    }
+
+    #[test]
+    fn skips_unreadable_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("foo-1.0-1-x86_64.pkg.tar.zst"), "package").unwrap();
+        std::os::unix::fs::symlink(
+            dir.path().join("missing"),
+            dir.path().join("bar-1.0-1-x86_64.pkg.tar.zst"),
+        )
+        .unwrap();
+
+        let cache = PackageCache::scan(&[dir.path().to_path_buf()]).unwrap();
+        assert_eq!(cache.file_count(), 1);
+        assert_eq!(cache.versions("foo")[0].size, 7);
+        assert!(cache.versions("bar").is_empty());
+    }
+
+    #[test]
+    fn keeps_newest_versions_and_the_installed_one() {
+        let cache = PackageCache {
+            files: BTreeMap::from([(
+                "foo".to_string(),
+                vec![
+                    cached("/a", "foo", "3.0-1", 30),
+                    cached("/b", "foo", "3.0-1", 30),
+                    cached("/a", "foo", "2.0-1", 20),
+                    cached("/b", "foo", "2.0-1", 20),
+                    cached("/a", "foo", "1.0-1", 10),
+                ],
+            )]),
+        };
+
+        // Both copies of the newest version are kept
+        let candidates = cache.cleanup_candidates(1, &[]);
+        let versions: Vec<&str> = candidates.iter().map(|f| f.version.as_str()).collect();
+        assert_eq!(versions, ["2.0-1", "2.0-1", "1.0-1"]);
+
+        let candidates = cache.cleanup_candidates(1, &[installed("foo", "1.0-1")]);
+        let versions: Vec<&str> = candidates.iter().map(|f| f.version.as_str()).collect();
+        assert_eq!(versions, ["2.0-1", "2.0-1"]);
+
+        assert!(cache.cleanup_candidates(3, &[]).is_empty());
+    }
+}
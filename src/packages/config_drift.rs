@@ -0,0 +1,347 @@
+use crate::packages::{models::Package, source::PackageSource};
+use similar::TextDiff;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt, fs, io,
+    path::Path,
+};
+
+/// Why pacman left a second copy of a config file next to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftKind {
+    /// The package shipped a new version of a locally modified file, which
+    /// pacman installed next to it instead of overwriting the changes
+    Pacnew,
+    /// A locally modified file was saved aside when its package was removed
+    /// or stopped shipping it. pacman numbers the copy, like `.pacsave.0`,
+    /// if an earlier one is still there.
+    Pacsave(Option<u32>),
+}
+
+impl fmt::Display for DriftKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DriftKind::Pacnew => write!(f, "pacnew"),
+            DriftKind::Pacsave(None) => write!(f, "pacsave"),
+            DriftKind::Pacsave(Some(n)) => write!(f, "pacsave.{}", n),
+        }
+    }
+}
+
+/// What to do about a drifted config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftAction {
+    /// Keep the locally modified version and drop the other one
+    KeepOld,
+    /// Replace the locally modified version with the package's one
+    TakeNew,
+    /// Open both versions in the configured merge tool
+    Merge,
+}
+
+/// A config file with a `.pacnew` or `.pacsave` copy.
+#[derive(Debug, Clone)]
+pub struct DriftFile {
+    /// The config file, relative to the installation root
+    pub path: String,
+    pub kind: DriftKind,
+    /// The installed package listing the file as a backup file
+    pub owner: Option<String>,
+    /// Unified diff from the local version to the package's version
+    pub diff: Result<String, String>,
+}
+
+impl DriftFile {
+    fn new(root: &Path, path: String, kind: DriftKind, owner: Option<String>) -> Self {
+        let mut file = Self {
+            path,
+            kind,
+            owner,
+            diff: Ok(String::new()),
+        };
+        file.diff = file.compute_diff(root);
+        file
+    }
+
+    /// The `.pacnew` or `.pacsave` file, relative to the installation root
+    pub fn drift_path(&self) -> String {
+        format!("{}.{}", self.path, self.kind)
+    }
+
+    /// The locally modified version and the package's version, relative to
+    /// the installation root. For a `.pacsave` the config file itself is the
+    /// package's version, if it exists at all.
+    pub fn versions(&self) -> (String, String) {
+        match self.kind {
+            DriftKind::Pacnew => (self.path.clone(), self.drift_path()),
+            DriftKind::Pacsave(_) => (self.drift_path(), self.path.clone()),
+        }
+    }
+
+    /// Command resolving the file: removing the copy if the current file is
+    /// kept, moving it over the config file otherwise, or opening both in
+    /// the merge tool. `None` if the source is read-only.
+    pub fn resolve_command(&self, source: &dyn PackageSource, action: DriftAction) -> Option<Vec<String>> {
+        let root = source.root();
+        let drift_path = root.join(self.drift_path());
+        match (action, self.kind) {
+            (DriftAction::Merge, _) => {
+                let (old, new) = self.versions();
+                source.merge_files_command(&root.join(old), &root.join(new))
+            }
+            (DriftAction::KeepOld, DriftKind::Pacnew) | (DriftAction::TakeNew, DriftKind::Pacsave(_)) => {
+                source.remove_files_command(&[drift_path.as_path()])
+            }
+            _ => source.move_file_command(&drift_path, &root.join(&self.path)),
+        }
+    }
+
+    /// Name of the package list entry the file is shown under: its owner, or
+    /// the config file itself if no installed package owns it
+    pub fn entry_name(&self) -> &str {
+        self.owner.as_deref().unwrap_or(&self.path)
+    }
+
+    fn compute_diff(&self, root: &Path) -> Result<String, String> {
+        let (old, new) = self.versions();
+        let read = |path: &str| match fs::read(root.join(path)) {
+            Ok(bytes) => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+            // A .pacsave of a removed package has nothing to compare with
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(format!("Cannot read /{}: {}", path, e)),
+        };
+        let (old_text, new_text) = (read(&old)?, read(&new)?);
+        Ok(TextDiff::from_lines(&old_text, &new_text)
+            .unified_diff()
+            .header(&format!("/{}", old), &format!("/{}", new))
+            .to_string())
+    }
+}
+
+/// The `.pacnew` and `.pacsave` files on the system, like `pacdiff` finds.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigDrift {
+    /// Sorted by config file path
+    files: Vec<DriftFile>,
+}
+
+impl ConfigDrift {
+    /// Looks for copies next to the backup files of all installed packages
+    /// and anywhere under `/etc`. Unreadable directories are skipped.
+    pub fn scan(source: &dyn PackageSource, packages: &[Package]) -> Self {
+        let root = source.root();
+        let mut owners: HashMap<String, String> = HashMap::new();
+        for pkg in packages.iter().filter(|p| p.is_installed) {
+            let Ok(files) = source.package_files(&pkg.name, &pkg.version) else {
+                continue;
+            };
+            for backup in files.backup {
+                owners.insert(backup.path, pkg.name.clone());
+            }
+        }
+
+        let mut found: BTreeSet<String> = BTreeSet::new();
+        // Backup files outside /etc: look next to them, as numbered copies
+        // cannot be guessed
+        let dirs: BTreeSet<&str> = owners
+            .keys()
+            .filter(|path| !path.starts_with("etc/"))
+            .filter_map(|path| path.rsplit_once('/').map(|(dir, _)| dir))
+            .collect();
+        for dir in dirs {
+            let Ok(entries) = fs::read_dir(root.join(dir)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = format!("{}/{}", dir, entry.file_name().to_string_lossy());
+                let is_copy = split_drift_path(&path).is_some_and(|(p, _)| owners.contains_key(p));
+                if is_copy && entry.file_type().is_ok_and(|t| t.is_file()) {
+                    found.insert(path);
+                }
+            }
+        }
+        find_drift_files(&root, "etc", &mut found);
+
+        let files = found
+            .into_iter()
+            .filter_map(|drift_path| {
+                let (path, kind) = split_drift_path(&drift_path)?;
+                let owner = owners.get(path).cloned();
+                Some(DriftFile::new(&root, path.to_string(), kind, owner))
+            })
+            .collect();
+        Self { files }
+    }
+
+    pub fn files(&self) -> &[DriftFile] {
+        &self.files
+    }
+
+    /// Drifted files shown under the given package list entry.
+    pub fn for_entry<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a DriftFile> {
+        self.files.iter().filter(move |f| f.entry_name() == name)
+    }
+
+    /// Compares a copy with its config file again, e.g. after a merge.
+    pub fn refresh(&mut self, root: &Path, file: &DriftFile) {
+        if let Some(f) = self
+            .files
+            .iter_mut()
+            .find(|f| f.path == file.path && f.kind == file.kind)
+        {
+            f.diff = f.compute_diff(root);
+        }
+    }
+
+    /// Forgets a copy that was resolved.
+    pub fn remove(&mut self, file: &DriftFile) {
+        self.files.retain(|f| f.path != file.path || f.kind != file.kind);
+    }
+
+    /// One entry per package owning a drifted file, and one per drifted file
+    /// without an installed owner.
+    pub fn packages(&self, installed: &[Package]) -> Vec<Package> {
+        let installed: HashMap<&str, &Package> =
+            installed.iter().map(|p| (p.name.as_str(), p)).collect();
+        let names: BTreeSet<&str> = self.files.iter().map(DriftFile::entry_name).collect();
+        names
+            .into_iter()
+            .map(|name| match installed.get(name) {
+                Some(&pkg) => pkg.clone(),
+                None => {
+                    let mut pkg = Package::unknown(name);
+                    pkg.description = "Not owned by an installed package".to_string();
+                    pkg
+                }
+            })
+            .collect()
+    }
+}
+
+// Collects `.pacnew` and `.pacsave(.N)` files below `dir` (relative to `root`)
+// without following symlinks
+fn find_drift_files(root: &Path, dir: &str, found: &mut BTreeSet<String>) {
+    let Ok(entries) = fs::read_dir(root.join(dir)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = format!("{}/{}", dir, entry.file_name().to_string_lossy());
+        if file_type.is_dir() {
+            find_drift_files(root, &path, found);
+        } else if file_type.is_file() && split_drift_path(&path).is_some() {
+            found.insert(path);
+        }
+    }
+}
+
+fn split_drift_path(drift_path: &str) -> Option<(&str, DriftKind)> {
+    if let Some(path) = drift_path.strip_suffix(".pacnew") {
+        return Some((path, DriftKind::Pacnew));
+    }
+    if let Some(path) = drift_path.strip_suffix(".pacsave") {
+        return Some((path, DriftKind::Pacsave(None)));
+    }
+    // Numbered copies, like pacdiff's `*.pacsave.[0-9]*`
+    let (rest, number) = drift_path.rsplit_once('.')?;
+    if !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let path = rest.strip_suffix(".pacsave")?;
+    Some((path, DriftKind::Pacsave(Some(number.parse().ok()?))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{AurConfig, PacmanConfig},
+        packages::{
+            fixture::tests::{example, example_packages},
+            pacman::PacmanSource,
+        },
+    };
+    use std::process::Command;
+
+    #[tokio::test]
+    async fn finds_copies_on_the_example_system() {
+        let drift = ConfigDrift::scan(&example(), &example_packages().await);
+        let found: Vec<(String, Option<&str>)> = drift
+            .files()
+            .iter()
+            .map(|f| (f.drift_path(), f.owner.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("etc/bash.bashrc.pacnew".to_string(), Some("bash")),
+                ("etc/oldtool.conf.pacsave".to_string(), None),
+                ("etc/oldtool.conf.pacsave.0".to_string(), None),
+            ]
+        );
+        let diff = drift.files()[0].diff.as_ref().unwrap();
+        assert!(diff.contains("+++ /etc/bash.bashrc.pacnew"), "{}", diff);
+    }
+
+    #[test]
+    fn splits_drift_paths() {
+        assert_eq!(split_drift_path("etc/a.conf.pacnew"), Some(("etc/a.conf", DriftKind::Pacnew)));
+        assert_eq!(
+            split_drift_path("etc/a.conf.pacsave"),
+            Some(("etc/a.conf", DriftKind::Pacsave(None)))
+        );
+        assert_eq!(
+            split_drift_path("etc/a.conf.pacsave.12"),
+            Some(("etc/a.conf", DriftKind::Pacsave(Some(12))))
+        );
+        assert_eq!(split_drift_path("etc/a.conf.pacsave.old"), None);
+        assert_eq!(split_drift_path("etc/a.conf.pacsave."), None);
+        assert_eq!(split_drift_path("etc/a.conf.1"), None);
+    }
+
+    // Runs the resolution on a temporary root, without a privilege command
+    fn resolve(root: &Path, file: &DriftFile, action: DriftAction) {
+        let config = PacmanConfig {
+            root: root.to_path_buf(),
+            privilege_command: Vec::new(),
+            ..PacmanConfig::default()
+        };
+        let source = PacmanSource::new(config, AurConfig::default());
+        let command = file.resolve_command(&source, action).unwrap();
+        let status = Command::new(&command[0]).args(&command[1..]).status().unwrap();
+        assert!(status.success(), "{:?}", command);
+    }
+
+    #[test]
+    fn keeps_or_takes_the_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("etc")).unwrap();
+        let write = |path: &str, content: &str| fs::write(root.join(path), content).unwrap();
+        let read = |path: &str| fs::read_to_string(root.join(path)).ok();
+        let file = |kind| DriftFile::new(root, "etc/a.conf".to_string(), kind, None);
+
+        write("etc/a.conf", "mine");
+        write("etc/a.conf.pacnew", "theirs");
+        resolve(root, &file(DriftKind::Pacnew), DriftAction::KeepOld);
+        assert_eq!(read("etc/a.conf").as_deref(), Some("mine"));
+        assert_eq!(read("etc/a.conf.pacnew"), None);
+
+        write("etc/a.conf.pacnew", "theirs");
+        resolve(root, &file(DriftKind::Pacnew), DriftAction::TakeNew);
+        assert_eq!(read("etc/a.conf").as_deref(), Some("theirs"));
+        assert_eq!(read("etc/a.conf.pacnew"), None);
+
+        write("etc/a.conf.pacsave.0", "saved");
+        resolve(root, &file(DriftKind::Pacsave(Some(0))), DriftAction::KeepOld);
+        assert_eq!(read("etc/a.conf").as_deref(), Some("saved"));
+        assert_eq!(read("etc/a.conf.pacsave.0"), None);
+
+        write("etc/a.conf.pacsave", "older");
+        resolve(root, &file(DriftKind::Pacsave(None)), DriftAction::TakeNew);
+        assert_eq!(read("etc/a.conf").as_deref(), Some("saved"));
+        assert_eq!(read("etc/a.conf.pacsave"), None);
+    }
+}
@@ -0,0 +1,418 @@
+use crate::packages::{models::Package, vercmp::vercmp};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+};
+
+/// How many chains [`DependencyGraph::why_installed`] reports at most
+const MAX_CHAINS: usize = 3;
+
+/// A parsed dependency string such as `glibc>=2.38` or `python: for scripts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    /// Version constraint including its operator, e.g. `>=2.38`
+    pub constraint: Option<String>,
+    /// Reason given for an optional dependency
+    pub description: Option<String>,
+}
+
+impl Dependency {
+    pub fn parse(raw: &str) -> Self {
+        let (spec, description) = match raw.split_once(": ") {
+            Some((spec, desc)) => (spec.trim(), Some(desc.trim().to_string())),
+            None => (raw.trim(), None),
+        };
+        match spec.find(['<', '>', '=']) {
+            Some(idx) => Self {
+                name: spec[..idx].to_string(),
+                constraint: Some(spec[idx..].to_string()),
+                description,
+            },
+            None => Self {
+                name: spec.to_string(),
+                constraint: None,
+                description,
+            },
+        }
+    }
+
+    /// Whether a package of the given version meets the version constraint.
+    /// Like pacman, a constraint without pkgrel accepts any pkgrel.
+    pub fn is_satisfied_by(&self, version: &str) -> bool {
+        let Some(constraint) = &self.constraint else {
+            return true;
+        };
+        let Some((op, wanted)) = [">=", "<=", "=", "<", ">"]
+            .into_iter()
+            .find_map(|op| constraint.strip_prefix(op).map(|v| (op, v)))
+        else {
+            return false;
+        };
+        let order = vercmp(version, wanted);
+        match op {
+            ">=" => order != Ordering::Less,
+            "<=" => order != Ordering::Greater,
+            "=" => order == Ordering::Equal,
+            "<" => order == Ordering::Less,
+            _ => order == Ordering::Greater,
+        }
+    }
+}
+
+/// One package in a chain explaining why another package is installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainLink {
+    pub name: String,
+    /// Whether the previous package in the chain only optionally depends on this one
+    pub optional: bool,
+}
+
+/// Why a package is installed, see [`DependencyGraph::why_installed`].
+///
+/// Chains start at an explicitly installed package and end at the package
+/// being explained.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WhyInstalled {
+    Explicit,
+    /// Shortest chains of hard dependencies
+    Required(Vec<Vec<ChainLink>>),
+    /// Shortest chains that need at least one optional dependency, as no
+    /// chain of hard dependencies exists
+    OptionalOnly(Vec<Vec<ChainLink>>),
+    /// Nothing explicitly installed leads to the package
+    Unneeded,
+}
+
+/// Dependency relations between the installed packages.
+///
+/// Dependencies are resolved by name and through `provides`, so a package
+/// depending on `sh` is linked to whichever installed package provides it.
+/// Version constraints are not checked; the local database is assumed to be
+/// consistent, which pacman guarantees for everything it installed.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    depends: HashMap<String, Vec<String>>,
+    required_by: HashMap<String, Vec<String>>,
+    optional_deps: HashMap<String, Vec<String>>,
+    optional_for: HashMap<String, Vec<String>>,
+}
+
+impl DependencyGraph {
+    pub fn build(packages: &[Package]) -> Self {
+        // Map every name a package can be depended on by to its providers
+        let mut providers: HashMap<&str, Vec<&str>> = HashMap::new();
+        for pkg in packages {
+            providers.entry(pkg.name.as_str()).or_default().push(&pkg.name);
+            for provide in &pkg.provides {
+                let name = provide.split('=').next().unwrap_or(provide);
+                providers.entry(name).or_default().push(&pkg.name);
+            }
+        }
+        let resolve = |raw: &str| -> Vec<String> {
+            let dep = Dependency::parse(raw);
+            match providers.get(dep.name.as_str()) {
+                // A real package of that name wins over other providers
+                Some(names) if names.contains(&dep.name.as_str()) => vec![dep.name],
+                Some(names) => names.iter().map(|n| n.to_string()).collect(),
+                None => Vec::new(),
+            }
+        };
+
+        let mut graph = Self::default();
+        for pkg in packages {
+            let depends = unique(pkg.depends.iter().flat_map(|d| resolve(d)), &pkg.name);
+            let optional = unique(pkg.optional_deps.iter().flat_map(|d| resolve(d)), &pkg.name);
+
+            for dep in &depends {
+                graph.required_by.entry(dep.clone()).or_default().push(pkg.name.clone());
+            }
+            for dep in &optional {
+                graph.optional_for.entry(dep.clone()).or_default().push(pkg.name.clone());
+            }
+            graph.depends.insert(pkg.name.clone(), depends);
+            graph.optional_deps.insert(pkg.name.clone(), optional);
+        }
+        for names in graph.required_by.values_mut().chain(graph.optional_for.values_mut()) {
+            names.sort();
+        }
+        graph
+    }
+
+    /// Installed packages the given package directly depends on.
+    pub fn depends_on(&self, name: &str) -> &[String] {
+        self.depends.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Installed packages that directly depend on the given package.
+    pub fn required_by(&self, name: &str) -> &[String] {
+        self.required_by.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Installed packages the given package optionally depends on.
+    pub fn optional_deps(&self, name: &str) -> &[String] {
+        self.optional_deps.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Installed packages that list the given package as an optional dependency.
+    pub fn optional_for(&self, name: &str) -> &[String] {
+        self.optional_for.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Everything the given package pulls in, directly or transitively.
+    pub fn all_dependencies(&self, name: &str) -> BTreeSet<String> {
+        walk(name, |n| self.depends_on(n))
+    }
+
+    /// Everything that needs the given package, directly or transitively.
+    pub fn all_dependents(&self, name: &str) -> BTreeSet<String> {
+        walk(name, |n| self.required_by(n))
+    }
+
+    /// Explains why a package is installed through the shortest chains from
+    /// explicitly installed packages down to it. Optional dependencies are
+    /// only followed if no chain of hard dependencies exists.
+    pub fn why_installed(&self, name: &str, explicit: &HashSet<&str>) -> WhyInstalled {
+        if explicit.contains(name) {
+            return WhyInstalled::Explicit;
+        }
+        let chains = self.shortest_chains(name, explicit, false);
+        if !chains.is_empty() {
+            return WhyInstalled::Required(chains);
+        }
+        let chains = self.shortest_chains(name, explicit, true);
+        if !chains.is_empty() {
+            return WhyInstalled::OptionalOnly(chains);
+        }
+        WhyInstalled::Unneeded
+    }
+
+    // Breadth-first search from the package up through its dependents,
+    // stopping at the first level that reaches explicitly installed packages
+    fn shortest_chains(
+        &self,
+        name: &str,
+        explicit: &HashSet<&str>,
+        with_optional: bool,
+    ) -> Vec<Vec<ChainLink>> {
+        // Dependent -> the packages one step closer to `name` it depends on,
+        // and whether only optionally
+        let mut children: HashMap<&str, Vec<(&str, bool)>> = HashMap::new();
+        let mut depth: HashMap<&str, usize> = HashMap::from([(name, 0)]);
+        let mut frontier = vec![name];
+        let mut roots: Vec<&str> = Vec::new();
+
+        let mut level = 0;
+        while roots.is_empty() && !frontier.is_empty() {
+            level += 1;
+            let mut next = Vec::new();
+            for &node in &frontier {
+                let hard = self.required_by(node).iter().map(|d| (d.as_str(), false));
+                let optional = self
+                    .optional_for(node)
+                    .iter()
+                    .filter(|_| with_optional)
+                    .map(|d| (d.as_str(), true));
+                for (dependent, optional) in hard.chain(optional) {
+                    match depth.get(dependent) {
+                        Some(&d) if d < level => continue,
+                        Some(_) => {}
+                        None => {
+                            depth.insert(dependent, level);
+                            next.push(dependent);
+                        }
+                    }
+                    children.entry(dependent).or_default().push((node, optional));
+                }
+            }
+            roots = next.iter().copied().filter(|n| explicit.contains(n)).collect();
+            frontier = next.into_iter().filter(|n| !explicit.contains(n)).collect();
+        }
+
+        roots.sort_unstable();
+        let mut chains = Vec::new();
+        for root in roots {
+            let start = vec![ChainLink { name: root.to_string(), optional: false }];
+            collect_chains(root, name, start, &children, &mut chains);
+        }
+        chains
+    }
+}
+
+// Extends `chain` down to `target` along every shortest path
+fn collect_chains(
+    node: &str,
+    target: &str,
+    chain: Vec<ChainLink>,
+    children: &HashMap<&str, Vec<(&str, bool)>>,
+    chains: &mut Vec<Vec<ChainLink>>,
+) {
+    if chains.len() >= MAX_CHAINS {
+        return;
+    }
+    if node == target {
+        chains.push(chain);
+        return;
+    }
+    for &(child, optional) in children.get(node).map_or(&[][..], Vec::as_slice) {
+        let mut next = chain.clone();
+        next.push(ChainLink { name: child.to_string(), optional });
+        collect_chains(child, target, next, children, chains);
+    }
+}
+
+fn unique(names: impl Iterator<Item = String>, own_name: &str) -> Vec<String> {
+    let set: BTreeSet<String> = names.filter(|n| n != own_name).collect();
+    set.into_iter().collect()
+}
+
+// Breadth-first traversal that excludes the starting package itself
+fn walk<'a>(start: &str, next: impl Fn(&str) -> &'a [String]) -> BTreeSet<String> {
+    let mut seen = BTreeSet::new();
+    let mut queue = VecDeque::from([start.to_string()]);
+    while let Some(current) = queue.pop_front() {
+        for neighbour in next(&current) {
+            if neighbour != start && seen.insert(neighbour.clone()) {
+                queue.push_back(neighbour.clone());
+            }
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::fixture::tests::example_packages;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn chain(links: &[(&str, bool)]) -> Vec<ChainLink> {
+        links
+            .iter()
+            .map(|(name, optional)| ChainLink { name: name.to_string(), optional: *optional })
+            .collect()
+    }
+
+    fn explicit_names(packages: &[Package]) -> HashSet<&str> {
+        packages.iter().filter(|p| p.is_explicit).map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_constraints_and_descriptions() {
+        let dep = Dependency::parse("glibc>=2.38");
+        assert_eq!(dep.name, "glibc");
+        assert_eq!(dep.constraint.as_deref(), Some(">=2.38"));
+        assert_eq!(dep.description, None);
+
+        let dep = Dependency::parse("python: for scripts");
+        assert_eq!(dep.name, "python");
+        assert_eq!(dep.constraint, None);
+        assert_eq!(dep.description.as_deref(), Some("for scripts"));
+    }
+
+    #[test]
+    fn checks_version_constraints() {
+        assert!(Dependency::parse("glibc").is_satisfied_by("2.40-1"));
+        assert!(Dependency::parse("glibc>=2.38").is_satisfied_by("2.40-1"));
+        assert!(!Dependency::parse("glibc>=2.38").is_satisfied_by("2.37-1"));
+        assert!(Dependency::parse("lib<2").is_satisfied_by("1.9-1"));
+        assert!(!Dependency::parse("lib>1.0-2").is_satisfied_by("1.0-2"));
+        // Without a pkgrel any release of the version matches
+        assert!(Dependency::parse("lib=1.0").is_satisfied_by("1.0-3"));
+        assert!(!Dependency::parse("lib=1.0-2").is_satisfied_by("1.0-3"));
+        assert!(Dependency::parse("gtk3<=1:3.24.43").is_satisfied_by("1:3.24.43-4"));
+    }
+
+    #[tokio::test]
+    async fn resolves_dependencies_through_provides() {
+        let packages = example_packages().await;
+        let graph = DependencyGraph::build(&packages);
+
+        // firefox depends on sh, which bash provides
+        assert_eq!(graph.depends_on("firefox"), names(&["bash", "gtk3", "libpulse"]));
+        assert_eq!(graph.optional_deps("firefox"), names(&["libnotify"]));
+        // Dependencies that are not installed are left out
+        assert!(graph.depends_on("yay").is_empty());
+    }
+
+    #[test]
+    fn prefers_a_real_package_over_providers() {
+        let provider = |name: &str, provides: &str| Package {
+            provides: vec![provides.to_string()],
+            ..Package::installed(name, false, &[])
+        };
+        let packages = [
+            Package::installed("app", true, &["sh", "java-runtime>=17", "lib>=1.0"]),
+            Package::installed("sh", false, &[]),
+            provider("bash", "sh"),
+            provider("jre17", "java-runtime=17"),
+            provider("jre21", "java-runtime=21"),
+            Package::installed("lib", false, &["lib"]),
+        ];
+        let graph = DependencyGraph::build(&packages);
+
+        assert_eq!(graph.depends_on("app"), names(&["jre17", "jre21", "lib", "sh"]));
+        assert!(graph.required_by("bash").is_empty());
+        // A package never depends on itself
+        assert!(graph.depends_on("lib").is_empty());
+    }
+
+    #[tokio::test]
+    async fn looks_up_reverse_dependencies() {
+        let packages = example_packages().await;
+        let graph = DependencyGraph::build(&packages);
+
+        assert_eq!(graph.required_by("glib2"), names(&["at-spi2-core", "gtk3", "libpulse"]));
+        assert_eq!(graph.required_by("bash"), names(&["company-tools", "firefox"]));
+        assert_eq!(graph.optional_for("libnotify"), names(&["firefox"]));
+        assert!(graph.required_by("firefox").is_empty());
+
+        let dependents: Vec<String> = graph.all_dependents("glibc").into_iter().collect();
+        assert_eq!(
+            dependents,
+            names(&[
+                "at-spi2-core",
+                "bash",
+                "company-tools",
+                "firefox",
+                "glib2",
+                "gtk3",
+                "libnotify",
+                "libpulse",
+            ])
+        );
+        // Cycles end the walk without including the starting package
+        let dependencies: Vec<String> = graph.all_dependencies("python-setuptools").into_iter().collect();
+        assert_eq!(dependencies, names(&["python-packaging"]));
+    }
+
+    #[tokio::test]
+    async fn explains_why_packages_are_installed() {
+        let packages = example_packages().await;
+        let graph = DependencyGraph::build(&packages);
+        let explicit = explicit_names(&packages);
+
+        assert_eq!(graph.why_installed("firefox", &explicit), WhyInstalled::Explicit);
+        // The shortest chain wins over the longer ones through glib2
+        assert_eq!(
+            graph.why_installed("glibc", &explicit),
+            WhyInstalled::Required(vec![chain(&[("bash", false), ("glibc", false)])])
+        );
+        assert_eq!(
+            graph.why_installed("glib2", &explicit),
+            WhyInstalled::Required(vec![
+                chain(&[("firefox", false), ("gtk3", false), ("glib2", false)]),
+                chain(&[("firefox", false), ("libpulse", false), ("glib2", false)]),
+            ])
+        );
+        assert_eq!(
+            graph.why_installed("libnotify", &explicit),
+            WhyInstalled::OptionalOnly(vec![chain(&[("firefox", false), ("libnotify", true)])])
+        );
+        assert_eq!(graph.why_installed("python-six", &explicit), WhyInstalled::Unneeded);
+        assert_eq!(graph.why_installed("python-packaging", &explicit), WhyInstalled::Unneeded);
+    }
+}
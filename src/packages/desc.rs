@@ -0,0 +1,119 @@
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+
+/// Parsed contents of an alpm `desc` file.
+///
+/// The local database (`local/<pkg>/desc`) and the sync databases
+/// (`<repo>.db` archives) use the same `%KEY%` section format, so a
+/// single struct covers both. Fields that only exist in one of them
+/// are left empty when reading the other.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Desc {
+    pub name: String,
+    pub version: String,
+    pub base: Option<String>,
+    pub description: String,
+    pub url: Option<String>,
+    pub arch: Option<String>,
+    pub build_date: Option<i64>,
+    pub install_date: Option<i64>,
+    pub packager: Option<String>,
+    /// Installed size in bytes (`%SIZE%` locally, `%ISIZE%` in sync DBs)
+    pub installed_size: Option<u64>,
+    /// Download size in bytes (`%CSIZE%`, sync DBs only)
+    pub download_size: Option<u64>,
+    /// `%REASON%`: 0 (or absent) means explicitly installed, 1 means dependency
+    pub reason: Option<u8>,
+    pub filename: Option<String>,
+    pub licenses: Vec<String>,
+    pub groups: Vec<String>,
+    pub depends: Vec<String>,
+    pub optdepends: Vec<String>,
+    pub conflicts: Vec<String>,
+    pub provides: Vec<String>,
+    pub replaces: Vec<String>,
+}
+
+/// Splits a `%KEY%`-style file into its sections.
+///
+/// Each section starts with a `%KEY%` line followed by one value per line
+/// and ends at the next blank line.
+pub fn parse_sections(content: &str) -> Vec<(&str, Vec<&str>)> {
+    let mut sections = Vec::new();
+    let mut current: Option<(&str, Vec<&str>)> = None;
+
+    for line in content.lines() {
+        if line.len() > 2 && line.starts_with('%') && line.ends_with('%') {
+            if let Some(section) = current.take() {
+                sections.push(section);
+            }
+            current = Some((&line[1..line.len() - 1], Vec::new()));
+        } else if line.is_empty() {
+            if let Some(section) = current.take() {
+                sections.push(section);
+            }
+        } else if let Some((_, values)) = current.as_mut() {
+            values.push(line);
+        }
+    }
+    if let Some(section) = current.take() {
+        sections.push(section);
+    }
+    sections
+}
+
+/// Parses the contents of a `desc` file.
+pub fn parse_desc(content: &str) -> Result<Desc, AppError> {
+    let mut desc = Desc::default();
+
+    for (key, values) in parse_sections(content) {
+        let first = values.first().map(|v| v.to_string());
+        let list = || values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        match key {
+            "NAME" => desc.name = first.unwrap_or_default(),
+            "VERSION" => desc.version = first.unwrap_or_default(),
+            "BASE" => desc.base = first,
+            "DESC" => desc.description = values.join(" "),
+            "URL" => desc.url = first,
+            "ARCH" => desc.arch = first,
+            "BUILDDATE" => desc.build_date = parse_number(key, first)?,
+            "INSTALLDATE" => desc.install_date = parse_number(key, first)?,
+            "PACKAGER" => desc.packager = first,
+            "SIZE" | "ISIZE" => desc.installed_size = parse_number(key, first)?,
+            "CSIZE" => desc.download_size = parse_number(key, first)?,
+            "REASON" => desc.reason = parse_number(key, first)?,
+            "FILENAME" => desc.filename = first,
+            "LICENSE" => desc.licenses = list(),
+            "GROUPS" => desc.groups = list(),
+            "DEPENDS" => desc.depends = list(),
+            "OPTDEPENDS" => desc.optdepends = list(),
+            "CONFLICTS" => desc.conflicts = list(),
+            "PROVIDES" => desc.provides = list(),
+            "REPLACES" => desc.replaces = list(),
+            _ => {}
+        }
+    }
+
+    if desc.name.is_empty() {
+        return Err(AppError::ParseError("desc entry is missing %NAME%".to_string()));
+    }
+    if desc.version.is_empty() {
+        return Err(AppError::ParseError(format!(
+            "desc entry for '{}' is missing %VERSION%",
+            desc.name
+        )));
+    }
+    Ok(desc)
+}
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: Option<String>) -> Result<Option<T>, AppError> {
+    match value {
+        Some(v) => v
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| AppError::ParseError(format!("Invalid %{}% value: {}", key, v))),
+        None => Ok(None),
+    }
+}
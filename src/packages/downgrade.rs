@@ -0,0 +1,162 @@
+use crate::packages::{deps::Dependency, desc::Desc, models::Package, vercmp::vercmp};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+};
+
+/// What installing another version of an installed package with
+/// `pacman -U` would change.
+#[derive(Debug, Clone)]
+pub struct InstallPreview {
+    pub name: String,
+    /// The installed version, `None` if the package is not installed
+    pub from: Option<String>,
+    pub to: String,
+    /// Installed packages whose dependency on this package the new version
+    /// no longer satisfies, mapped to their dependency strings
+    pub broken_dependents: BTreeMap<String, Vec<String>>,
+    /// Dependencies of the new version the installed packages do not
+    /// satisfy, with the installed version of the dependency if any
+    pub unmet_depends: Vec<(String, Option<String>)>,
+}
+
+impl InstallPreview {
+    /// Checks the package described by `desc` against the installed packages.
+    pub fn compute(desc: &Desc, installed: &[Package]) -> Self {
+        let from = installed
+            .iter()
+            .find(|p| p.name == desc.name)
+            .map(|p| p.version.clone());
+
+        let mut broken_dependents: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for pkg in installed.iter().filter(|p| p.name != desc.name) {
+            for raw in &pkg.depends {
+                let dep = Dependency::parse(raw);
+                if dep.name == desc.name && !dep.is_satisfied_by(&desc.version) {
+                    broken_dependents.entry(pkg.name.clone()).or_default().push(raw.clone());
+                }
+            }
+        }
+
+        // The new version replaces the installed one, so it cannot satisfy
+        // its own dependencies through the old version
+        let others: Vec<&Package> = installed.iter().filter(|p| p.name != desc.name).collect();
+        let by_name: HashMap<&str, &Package> =
+            others.iter().map(|p| (p.name.as_str(), *p)).collect();
+        let unmet_depends = desc
+            .depends
+            .iter()
+            .filter(|raw| !is_satisfied(&Dependency::parse(raw), &others))
+            .map(|raw| {
+                let installed = by_name
+                    .get(Dependency::parse(raw).name.as_str())
+                    .map(|p| p.version.clone());
+                (raw.clone(), installed)
+            })
+            .collect();
+
+        Self {
+            name: desc.name.clone(),
+            from,
+            to: desc.version.clone(),
+            broken_dependents,
+            unmet_depends,
+        }
+    }
+
+    /// How the new version compares to the installed one, `None` if the
+    /// package is not installed.
+    pub fn direction(&self) -> Option<Ordering> {
+        self.from.as_deref().map(|from| vercmp(&self.to, from))
+    }
+
+    /// Whether pacman would install the version without breaking dependencies.
+    pub fn is_safe(&self) -> bool {
+        self.broken_dependents.is_empty() && self.unmet_depends.is_empty()
+    }
+}
+
+// A dependency is met by a package of that name or a provider. Unversioned
+// provisions only satisfy unversioned dependencies, as in pacman.
+fn is_satisfied(dep: &Dependency, installed: &[&Package]) -> bool {
+    installed.iter().any(|pkg| {
+        if pkg.name == dep.name && dep.is_satisfied_by(&pkg.version) {
+            return true;
+        }
+        pkg.provides.iter().any(|provide| match provide.split_once('=') {
+            Some((name, version)) => name == dep.name && dep.is_satisfied_by(version),
+            None => *provide == dep.name && dep.constraint.is_none(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn desc(name: &str, version: &str, depends: &[&str]) -> Desc {
+        Desc {
+            name: name.to_string(),
+            version: version.to_string(),
+            depends: depends.iter().map(|d| d.to_string()).collect(),
+            ..Desc::default()
+        }
+    }
+
+    fn installed(name: &str, version: &str, depends: &[&str]) -> Package {
+        Package {
+            version: version.to_string(),
+            ..Package::installed(name, true, depends)
+        }
+    }
+
+    #[test]
+    fn reports_dependents_the_old_version_breaks() {
+        let installed = [
+            installed("lib", "2.1-1", &[]),
+            installed("app", "1.0-1", &["lib>=2.0"]),
+            installed("tool", "1.0-1", &["lib"]),
+        ];
+        let preview = InstallPreview::compute(&desc("lib", "1.9-1", &[]), &installed);
+        assert_eq!(preview.from.as_deref(), Some("2.1-1"));
+        assert_eq!(preview.direction(), Some(Ordering::Less));
+        assert_eq!(preview.broken_dependents.keys().collect::<Vec<_>>(), ["app"]);
+        assert_eq!(preview.broken_dependents["app"], ["lib>=2.0"]);
+        assert!(!preview.is_safe());
+
+        let preview = InstallPreview::compute(&desc("lib", "2.0-5", &[]), &installed);
+        assert_eq!(preview.direction(), Some(Ordering::Less));
+        assert!(preview.is_safe());
+    }
+
+    #[test]
+    fn reports_unmet_dependencies_of_the_new_version() {
+        let mut bash = installed("bash", "5.2.037-1", &[]);
+        bash.provides = vec!["sh=5.2".to_string()];
+        let installed = [
+            installed("glibc", "2.39-1", &[]),
+            installed("foo", "2.0-1", &[]),
+            bash,
+        ];
+        let preview = InstallPreview::compute(
+            &desc("foo", "1.0-1", &["glibc>=2.40", "sh>=5", "foo-data", "foo>=1.5"]),
+            &installed,
+        );
+        assert_eq!(
+            preview.unmet_depends,
+            [
+                ("glibc>=2.40".to_string(), Some("2.39-1".to_string())),
+                ("foo-data".to_string(), None),
+                ("foo>=1.5".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn previews_packages_that_are_not_installed() {
+        let preview = InstallPreview::compute(&desc("foo", "1.0-1", &[]), &[]);
+        assert_eq!(preview.from, None);
+        assert_eq!(preview.direction(), None);
+        assert!(preview.is_safe());
+    }
+}
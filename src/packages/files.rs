@@ -0,0 +1,149 @@
+use crate::packages::{models::Package, source::PackageSource};
+use std::{fs, path::Path};
+
+/// A file owned by a package, checked against the installation root.
+#[derive(Debug, Clone)]
+pub struct FileStatus {
+    /// Path relative to the installation root; directories end with `/`
+    pub path: String,
+    /// Size in bytes, `None` if the file is missing
+    pub size: Option<u64>,
+}
+
+impl FileStatus {
+    pub fn is_dir(&self) -> bool {
+        self.path.ends_with('/')
+    }
+
+    pub fn exists(&self) -> bool {
+        self.size.is_some()
+    }
+}
+
+/// Looks up the given package files under `root`, without following symlinks.
+pub fn file_statuses(root: &Path, files: &[String]) -> Vec<FileStatus> {
+    files
+        .iter()
+        .map(|path| FileStatus {
+            path: path.clone(),
+            size: fs::symlink_metadata(root.join(path)).ok().map(|m| m.len()),
+        })
+        .collect()
+}
+
+/// Index from installed paths to the packages owning them, like `pacman -Qo`.
+#[derive(Debug, Default)]
+pub struct FileOwners {
+    /// (path, owner) pairs sorted by path; directories can have several owners
+    entries: Vec<(String, String)>,
+}
+
+impl FileOwners {
+    /// Reads the file lists of all installed packages.
+    /// Packages whose file list cannot be read are skipped.
+    pub fn build(source: &dyn PackageSource, packages: &[Package]) -> Self {
+        let mut entries = Vec::new();
+        for pkg in packages.iter().filter(|p| p.is_installed) {
+            let Ok(files) = source.package_files(&pkg.name, &pkg.version) else {
+                continue;
+            };
+            entries.extend(files.files.into_iter().map(|path| (path, pkg.name.clone())));
+        }
+        entries.sort();
+        Self { entries }
+    }
+
+    /// Number of indexed (path, owner) pairs
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether an installed package owns the path; directories end with `/`
+    pub fn contains(&self, path: &str) -> bool {
+        self.entries
+            .binary_search_by(|(p, _)| p.as_str().cmp(path))
+            .is_ok()
+    }
+
+    /// Finds the owners of a path or, for a bare name like `rg`, of every
+    /// file with that name.
+    pub fn search(&self, query: &str) -> Vec<(String, String)> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        if query.contains('/') {
+            let path = query.trim_start_matches('/');
+            let dir = format!("{}/", path.trim_end_matches('/'));
+            self.entries
+                .iter()
+                .filter(|(p, _)| *p == path || *p == dir)
+                .cloned()
+                .collect()
+        } else {
+            self.entries
+                .iter()
+                .filter(|(p, _)| !p.ends_with('/') && p.rsplit('/').next() == Some(query))
+                .cloned()
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::fixture::tests::{example, example_packages};
+
+    async fn example_owners() -> FileOwners {
+        FileOwners::build(&example(), &example_packages().await)
+    }
+
+    fn owner_pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(path, owner)| (path.to_string(), owner.to_string())).collect()
+    }
+
+    #[tokio::test]
+    async fn search_by_path_finds_the_owner() {
+        let owners = example_owners().await;
+
+        assert_eq!(owners.search("/usr/bin/yay"), owner_pairs(&[("usr/bin/yay", "yay")]));
+        assert_eq!(owners.search(" usr/bin/ctool "), owner_pairs(&[("usr/bin/ctool", "company-tools")]));
+        assert!(owners.search("/usr/bin/nothing").is_empty());
+    }
+
+    #[tokio::test]
+    async fn search_by_directory_lists_every_owner() {
+        let owners = example_owners().await;
+
+        let expected = owner_pairs(&[("etc/", "bash"), ("etc/", "company-tools")]);
+        assert_eq!(owners.search("/etc"), expected);
+        assert_eq!(owners.search("/etc/"), expected);
+    }
+
+    #[tokio::test]
+    async fn search_by_name_matches_files_only() {
+        let owners = example_owners().await;
+
+        assert_eq!(
+            owners.search("firefox"),
+            owner_pairs(&[("usr/bin/firefox", "firefox"), ("usr/lib/firefox/firefox", "firefox")])
+        );
+        assert_eq!(owners.search("sh"), owner_pairs(&[("usr/bin/sh", "bash")]));
+        assert!(owners.search("   ").is_empty());
+    }
+
+    #[tokio::test]
+    async fn contains_only_indexed_paths() {
+        let owners = example_owners().await;
+
+        assert!(owners.contains("usr/bin/bash"));
+        assert!(owners.contains("etc/company-tools.d/"));
+        assert!(!owners.contains("etc/company-tools.d"));
+        assert!(!owners.contains("usr/bin/zsh"));
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{
+    error::AppError,
+    packages::{
+        aur::{AurInfo, AurLookup, AurResponse},
+        desc::Desc,
+        local_db::{parse_mtree, MtreeEntry, PackageFiles},
+        pacman_log::PacmanLog,
+        source::{BoxFuture, PackageSource},
+        sync_db::SyncPackage,
+    },
+};
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Deterministic package source that reads captured data from a directory.
+///
+/// The directory may contain:
+/// - `local.json`: array of local `desc` entries (required)
+/// - `sync.json`: array of `{ "repository": ..., "packages": [desc, ...] }`,
+///   in repository priority order
+/// - `aur.json`: a captured AUR RPC `info` response
+/// - `pacman.log`: a pacman log file
+/// - `files.json`: object mapping package names to `{ "files": [...], "backup": [...] }`
+/// - `mtree/<name>`: uncompressed `mtree` entries of installed packages
+/// - `root/`: the installation root the package files are checked against
+/// - `cache/`: the package cache directory
+///
+/// `desc` entries use the field names of [`Desc`]; every field except
+/// `name` and `version` may be omitted.
+pub struct FixtureSource {
+    dir: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct FixtureRepo {
+    repository: String,
+    packages: Vec<Desc>,
+}
+
+impl FixtureSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn read<T: DeserializeOwned>(&self, file: &str) -> Result<T, AppError> {
+        let content = fs::read_to_string(self.dir.join(file))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    // Optional files default to empty data when they are missing
+    fn read_optional<T: DeserializeOwned + Default>(&self, file: &str) -> Result<T, AppError> {
+        if self.dir.join(file).exists() {
+            self.read(file)
+        } else {
+            Ok(T::default())
+        }
+    }
+}
+
+impl PackageSource for FixtureSource {
+    fn local_packages(&self, _warnings: &mut Vec<String>) -> Result<Vec<Desc>, AppError> {
+        self.read("local.json")
+    }
+
+    fn sync_packages(&self, _warnings: &mut Vec<String>) -> Result<Vec<SyncPackage>, AppError> {
+        let repos: Vec<FixtureRepo> = self.read_optional("sync.json")?;
+        Ok(repos
+            .into_iter()
+            .flat_map(|repo| {
+                let repository = repo.repository;
+                repo.packages.into_iter().map(move |desc| SyncPackage {
+                    repository: repository.clone(),
+                    desc,
+                })
+            })
+            .collect())
+    }
+
+    fn root(&self) -> PathBuf {
+        self.dir.join("root")
+    }
+
+    fn cache_dirs(&self) -> Vec<PathBuf> {
+        vec![self.dir.join("cache")]
+    }
+
+    fn package_files(&self, name: &str, _version: &str) -> Result<PackageFiles, AppError> {
+        let mut files: HashMap<String, PackageFiles> = self.read_optional("files.json")?;
+        Ok(files.remove(name).unwrap_or_default())
+    }
+
+    fn package_mtree(&self, name: &str, _version: &str) -> Result<Vec<MtreeEntry>, AppError> {
+        let content = fs::read_to_string(self.dir.join("mtree").join(name))?;
+        parse_mtree(&content)
+    }
+
+    fn pacman_log(&self) -> Result<PacmanLog, AppError> {
+        let path = self.dir.join("pacman.log");
+        if path.exists() {
+            PacmanLog::load(&path)
+        } else {
+            Ok(PacmanLog::default())
+        }
+    }
+
+    fn install_reason_command(&self, _package_names: &[String], _explicit: bool) -> Option<Vec<String>> {
+        None
+    }
+
+    fn remove_files_command(&self, _paths: &[&Path]) -> Option<Vec<String>> {
+        None
+    }
+
+    fn move_file_command(&self, _from: &Path, _to: &Path) -> Option<Vec<String>> {
+        None
+    }
+
+    fn merge_files_command(&self, _old: &Path, _new: &Path) -> Option<Vec<String>> {
+        None
+    }
+
+    fn install_files_command(&self, _paths: &[&Path]) -> Option<Vec<String>> {
+        None
+    }
+
+    fn aur_packages(&self, package_names: Vec<String>) -> BoxFuture<'_, Result<AurLookup, AppError>> {
+        Box::pin(async move {
+            if !self.dir.join("aur.json").exists() {
+                return Ok(AurLookup::default());
+            }
+            let response: AurResponse = self.read("aur.json")?;
+            let fetched_at = Utc::now();
+            let packages = response
+                .results
+                .into_iter()
+                .filter(|pkg| package_names.contains(&pkg.name))
+                .map(|package| {
+                    let info = AurInfo { package, fetched_at, stale: false };
+                    (info.package.name.clone(), info)
+                })
+                .collect();
+            Ok(AurLookup { packages, warnings: Vec::new() })
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::packages::{
+        models::{Package, Repository},
+        pacman::get_all_packages,
+    };
+
+    pub(crate) fn example() -> FixtureSource {
+        FixtureSource::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/example"))
+    }
+
+    /// The installed packages of the example fixture, without tags
+    pub(crate) async fn example_packages() -> Vec<Package> {
+        let source = example();
+        let mut warnings = Vec::new();
+        let sync_packages = source.sync_packages(&mut warnings).unwrap();
+        get_all_packages(&source, &sync_packages, &HashMap::new(), &mut warnings)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn loads_example_fixture() {
+        let source = example();
+        let mut warnings = Vec::new();
+        let sync_packages = source.sync_packages(&mut warnings).unwrap();
+        let tags = HashMap::from([("bash".to_string(), vec!["shell".to_string()])]);
+        let packages = get_all_packages(&source, &sync_packages, &tags, &mut warnings)
+            .await
+            .unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(sync_packages.len(), 11);
+        assert_eq!(packages.len(), 15);
+        assert!(packages.iter().all(|p| p.is_installed));
+
+        let mut explicit: Vec<&str> = packages
+            .iter()
+            .filter(|p| p.is_explicit)
+            .map(|p| p.name.as_str())
+            .collect();
+        explicit.sort_unstable();
+        assert_eq!(explicit, ["bash", "company-tools", "firefox", "ttf-legacy-fonts", "yay"]);
+
+        let repository = |name: &str| {
+            packages
+                .iter()
+                .find(|p| p.name == name)
+                .map(|p| p.repository.clone())
+                .unwrap()
+        };
+        assert_eq!(repository("glibc"), Repository::Sync("core".to_string()));
+        assert_eq!(repository("firefox"), Repository::Sync("extra".to_string()));
+        assert_eq!(repository("yay"), Repository::Aur);
+        assert_eq!(repository("company-tools"), Repository::Foreign);
+        let count = |repo: Repository| packages.iter().filter(|p| p.repository == repo).count();
+        assert_eq!(count(Repository::Sync("core".to_string())), 3);
+        assert_eq!(count(Repository::Sync("extra".to_string())), 6);
+        assert_eq!(count(Repository::Aur), 2);
+        assert_eq!(count(Repository::Foreign), 4);
+
+        let tags = |name: &str| packages.iter().find(|p| p.name == name).unwrap().tags.clone();
+        assert_eq!(tags("bash"), ["shell"]);
+        assert!(tags("glibc").is_empty());
+    }
+
+    #[test]
+    fn reads_package_files_and_mtree() {
+        let source = example();
+        let files = source.package_files("bash", "5.2.037-1").unwrap();
+        assert!(files.files.contains(&"usr/bin/bash".to_string()));
+        assert!(!source.package_mtree("bash", "5.2.037-1").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{
+    error::AppError,
+    packages::{
+        local_db::{MtreeEntry, MtreeKind},
+        source::PackageSource,
+    },
+};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    io::{self, Read},
+    os::unix::fs::MetadataExt,
+    path::Path,
+};
+
+/// How an installed path differs from its package's `mtree` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityProblem {
+    Missing,
+    /// The path exists but is not of the packaged type
+    WrongType { expected: MtreeKind },
+    Mode { expected: u32, actual: u32 },
+    /// Expected and actual `(uid, gid)`
+    Owner { expected: (u32, u32), actual: (u32, u32) },
+    Size { expected: u64, actual: u64 },
+    /// Same size, different contents
+    Checksum,
+    LinkTarget { expected: String, actual: String },
+    Unreadable(String),
+}
+
+impl fmt::Display for IntegrityProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntegrityProblem::Missing => write!(f, "missing"),
+            IntegrityProblem::WrongType { expected } => write!(f, "not a {}", expected),
+            IntegrityProblem::Mode { expected, actual } => {
+                write!(f, "mode {:o}, packaged {:o}", actual, expected)
+            }
+            IntegrityProblem::Owner { expected, actual } => write!(
+                f,
+                "owner {}:{}, packaged {}:{}",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            IntegrityProblem::Size { expected, actual } => {
+                write!(f, "size {} bytes, packaged {} bytes", actual, expected)
+            }
+            IntegrityProblem::Checksum => write!(f, "checksum mismatch"),
+            IntegrityProblem::LinkTarget { expected, actual } => {
+                write!(f, "links to {}, packaged {}", actual, expected)
+            }
+            IntegrityProblem::Unreadable(e) => write!(f, "unreadable: {}", e),
+        }
+    }
+}
+
+impl fmt::Display for MtreeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MtreeKind::File => write!(f, "file"),
+            MtreeKind::Dir => write!(f, "directory"),
+            MtreeKind::Link => write!(f, "symlink"),
+        }
+    }
+}
+
+/// A path that failed the integrity check.
+#[derive(Debug, Clone)]
+pub struct FileProblem {
+    /// Path relative to the installation root
+    pub path: String,
+    pub problem: IntegrityProblem,
+}
+
+/// Result of checking an installed package against its `mtree`, the
+/// equivalent of `pacman -Qkk`.
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    /// Number of paths checked
+    pub checked: usize,
+    pub problems: Vec<FileProblem>,
+}
+
+impl IntegrityReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Checks an installed package against its `mtree`. Like pacman, the size
+/// and contents of backup files are not checked, as they are meant to be
+/// edited.
+pub fn check_package(
+    source: &dyn PackageSource,
+    name: &str,
+    version: &str,
+) -> Result<IntegrityReport, AppError> {
+    let entries = source.package_mtree(name, version)?;
+    let backup: HashSet<String> = source
+        .package_files(name, version)
+        .map(|files| files.backup.into_iter().map(|b| b.path).collect())
+        .unwrap_or_default();
+    let root = source.root();
+
+    let problems = entries
+        .iter()
+        .flat_map(|entry| {
+            check_entry(&root, entry, backup.contains(&entry.path))
+                .into_iter()
+                .map(|problem| FileProblem {
+                    path: entry.path.clone(),
+                    problem,
+                })
+        })
+        .collect();
+    Ok(IntegrityReport {
+        checked: entries.len(),
+        problems,
+    })
+}
+
+fn check_entry(root: &Path, entry: &MtreeEntry, is_backup: bool) -> Vec<IntegrityProblem> {
+    let path = root.join(entry.path.trim_end_matches('/'));
+    let metadata = match fs::symlink_metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return vec![IntegrityProblem::Missing],
+        Err(e) => return vec![IntegrityProblem::Unreadable(e.to_string())],
+    };
+
+    let file_type = metadata.file_type();
+    let kind_matches = match entry.kind {
+        MtreeKind::File => file_type.is_file(),
+        MtreeKind::Dir => file_type.is_dir(),
+        MtreeKind::Link => file_type.is_symlink(),
+    };
+    if !kind_matches {
+        return vec![IntegrityProblem::WrongType { expected: entry.kind }];
+    }
+
+    let mut problems = Vec::new();
+    // Symlink permissions are meaningless on Linux
+    if let Some(expected) = entry.mode
+        && entry.kind != MtreeKind::Link
+        && metadata.mode() & 0o7777 != expected
+    {
+        problems.push(IntegrityProblem::Mode {
+            expected,
+            actual: metadata.mode() & 0o7777,
+        });
+    }
+    let actual_owner = (metadata.uid(), metadata.gid());
+    let expected_owner = (
+        entry.uid.unwrap_or(actual_owner.0),
+        entry.gid.unwrap_or(actual_owner.1),
+    );
+    if expected_owner != actual_owner {
+        problems.push(IntegrityProblem::Owner {
+            expected: expected_owner,
+            actual: actual_owner,
+        });
+    }
+
+    match entry.kind {
+        MtreeKind::Link => {
+            let actual = fs::read_link(&path)
+                .map(|target| target.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if let Some(expected) = &entry.link
+                && *expected != actual
+            {
+                problems.push(IntegrityProblem::LinkTarget {
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        MtreeKind::File if !is_backup => {
+            if let Some(expected) = entry.size
+                && expected != metadata.len()
+            {
+                problems.push(IntegrityProblem::Size {
+                    expected,
+                    actual: metadata.len(),
+                });
+            } else if let Some(problem) = check_contents(&path, entry) {
+                problems.push(problem);
+            }
+        }
+        _ => {}
+    }
+    problems
+}
+
+// Prefers SHA-256 over MD5 when the mtree records both
+fn check_contents(path: &Path, entry: &MtreeEntry) -> Option<IntegrityProblem> {
+    let (expected, actual) = match (&entry.sha256, &entry.md5) {
+        (Some(expected), _) => (expected, file_digest::<Sha256>(path)),
+        (None, Some(expected)) => (expected, file_digest::<Md5>(path)),
+        (None, None) => return None,
+    };
+    match actual {
+        Ok(actual) if actual.eq_ignore_ascii_case(expected) => None,
+        Ok(_) => Some(IntegrityProblem::Checksum),
+        Err(e) => Some(IntegrityProblem::Unreadable(e.to_string())),
+    }
+}
+
+/// Hex digest of a file's contents, read in chunks.
+pub fn file_digest<D: Digest>(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = D::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::fixture::FixtureSource;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    const HELLO_MD5: &str = "5d41402abc4b2a76b9719d911017c592";
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    const MTREE: &str = "#mtree
+/set type=file mode=644
+./.PKGINFO size=100 md5digest=00000000000000000000000000000000
+./etc type=dir mode=755
+./etc/app.conf size=3 md5digest=900150983cd24fb0d6963f7d28e17f72
+./usr type=dir mode=755
+./usr/bin type=dir mode=755
+./usr/bin/app mode=755 size=5 sha256digest=SHA
+./usr/bin/both size=5 md5digest=00000000000000000000000000000000 sha256digest=SHA
+./usr/bin/legacy size=5 md5digest=MD5
+./usr/bin/wrongsum size=5 sha256digest=SHA
+./usr/bin/wrongsize size=5 sha256digest=SHA
+./usr/bin/wrongmode mode=755 size=5 sha256digest=SHA
+./usr/bin/link type=link link=app
+./usr/bin/wronglink type=link link=app
+./usr/bin/notadir type=dir mode=755
+./usr/bin/notafile size=5 sha256digest=SHA
+./usr/bin/gone size=5 sha256digest=SHA
+";
+
+    fn write_file(root: &Path, path: &str, content: &str, mode: u32) {
+        let path = root.join(path);
+        fs::write(&path, content).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    // A fixture directory with one package, `app`, whose installed files
+    // deviate from its mtree in every checked way
+    fn app_fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("mtree")).unwrap();
+        let mtree = MTREE
+            .replace("=SHA", &format!("={}", HELLO_SHA256))
+            .replace("=MD5", &format!("={}", HELLO_MD5));
+        fs::write(dir.path().join("mtree/app"), mtree).unwrap();
+        fs::write(
+            dir.path().join("files.json"),
+            r#"{"app": {"backup": [{"path": "etc/app.conf", "hash": "900150983cd24fb0d6963f7d28e17f72"}]}}"#,
+        )
+        .unwrap();
+
+        let root = dir.path().join("root");
+        for path in ["etc", "usr", "usr/bin"] {
+            fs::create_dir_all(root.join(path)).unwrap();
+            fs::set_permissions(root.join(path), fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        // Edited backup file with a changed mode
+        write_file(&root, "etc/app.conf", "edited", 0o600);
+        write_file(&root, "usr/bin/app", "hello", 0o755);
+        write_file(&root, "usr/bin/both", "hello", 0o644);
+        write_file(&root, "usr/bin/legacy", "hello", 0o644);
+        write_file(&root, "usr/bin/wrongsum", "world", 0o644);
+        write_file(&root, "usr/bin/wrongsize", "hi", 0o644);
+        write_file(&root, "usr/bin/wrongmode", "hello", 0o644);
+        write_file(&root, "usr/bin/notadir", "", 0o755);
+        fs::create_dir(root.join("usr/bin/notafile")).unwrap();
+        symlink("app", root.join("usr/bin/link")).unwrap();
+        symlink("other", root.join("usr/bin/wronglink")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn reports_every_mismatch_against_the_mtree() {
+        let dir = app_fixture();
+        let report = check_package(&FixtureSource::new(dir.path()), "app", "1.0-1").unwrap();
+        let problems: Vec<(&str, &IntegrityProblem)> =
+            report.problems.iter().map(|p| (p.path.as_str(), &p.problem)).collect();
+
+        // Only the mode of the edited backup file counts, and the wrong
+        // MD5 of usr/bin/both is ignored in favour of its SHA-256
+        assert_eq!(report.checked, 15);
+        assert_eq!(
+            problems,
+            [
+                ("etc/app.conf", &IntegrityProblem::Mode { expected: 0o644, actual: 0o600 }),
+                ("usr/bin/wrongsum", &IntegrityProblem::Checksum),
+                ("usr/bin/wrongsize", &IntegrityProblem::Size { expected: 5, actual: 2 }),
+                ("usr/bin/wrongmode", &IntegrityProblem::Mode { expected: 0o755, actual: 0o644 }),
+                (
+                    "usr/bin/wronglink",
+                    &IntegrityProblem::LinkTarget { expected: "app".to_string(), actual: "other".to_string() }
+                ),
+                ("usr/bin/notadir", &IntegrityProblem::WrongType { expected: MtreeKind::Dir }),
+                ("usr/bin/notafile", &IntegrityProblem::WrongType { expected: MtreeKind::File }),
+                ("usr/bin/gone", &IntegrityProblem::Missing),
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_without_an_mtree() {
+        let dir = app_fixture();
+        assert!(check_package(&FixtureSource::new(dir.path()), "other", "1.0-1").is_err());
+    }
+}
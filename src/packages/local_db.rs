@@ -0,0 +1,302 @@
+use crate::{
+    config::PacmanConfig,
+    error::AppError,
+    packages::desc::{parse_desc, parse_sections, Desc},
+};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    path::PathBuf,
+};
+
+/// Reader for pacman's local database (`<dbpath>/local/<name>-<version>/`).
+pub struct LocalDb {
+    db_path: PathBuf,
+}
+
+/// A file listed in the `%BACKUP%` section of a package's `files` entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackupFile {
+    /// Path relative to the installation root
+    pub path: String,
+    /// MD5 checksum of the file as shipped by the package
+    pub hash: String,
+}
+
+/// Contents of a package's `files` entry.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PackageFiles {
+    /// Owned paths relative to the installation root; directories end with `/`
+    pub files: Vec<String>,
+    pub backup: Vec<BackupFile>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MtreeKind {
+    File,
+    Dir,
+    Link,
+}
+
+/// A single path recorded in a package's `mtree` entry.
+#[derive(Debug, Clone)]
+pub struct MtreeEntry {
+    /// Path relative to the installation root
+    pub path: String,
+    pub kind: MtreeKind,
+    pub mode: Option<u32>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub size: Option<u64>,
+    pub md5: Option<String>,
+    pub sha256: Option<String>,
+    pub link: Option<String>,
+}
+
+impl LocalDb {
+    pub fn new(db_path: impl Into<PathBuf>) -> Self {
+        Self {
+            db_path: db_path.into(),
+        }
+    }
+
+    pub fn from_config(config: &PacmanConfig) -> Self {
+        Self::new(&config.db_path)
+    }
+
+    fn local_dir(&self) -> PathBuf {
+        self.db_path.join("local")
+    }
+
+    fn entry_dir(&self, name: &str, version: &str) -> PathBuf {
+        self.local_dir().join(format!("{}-{}", name, version))
+    }
+
+    /// Reads the `desc` entry of every installed package.
+    ///
+    /// Entries that cannot be read or parsed are skipped with a warning,
+    /// matching how pacman itself ignores broken database entries.
+    pub fn read_packages(&self, warnings: &mut Vec<String>) -> Result<Vec<Desc>, AppError> {
+        let local_dir = self.local_dir();
+        let dir = fs::read_dir(&local_dir).map_err(|e| {
+            AppError::ParseError(format!(
+                "Failed to read local database at {}: {}",
+                local_dir.display(),
+                e
+            ))
+        })?;
+
+        let mut packages = Vec::new();
+        for entry in dir.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue; // e.g. ALPM_DB_VERSION
+            }
+            match fs::read_to_string(path.join("desc"))
+                .map_err(AppError::from)
+                .and_then(|content| parse_desc(&content))
+            {
+                Ok(desc) => packages.push(desc),
+                Err(e) => warnings.push(format!(
+                    "Skipped local database entry {}: {}",
+                    path.display(),
+                    e
+                )),
+            }
+        }
+        Ok(packages)
+    }
+
+    /// Reads the `files` entry of an installed package.
+    pub fn read_files(&self, name: &str, version: &str) -> Result<PackageFiles, AppError> {
+        let content = fs::read_to_string(self.entry_dir(name, version).join("files"))?;
+        Ok(parse_files(&content))
+    }
+
+    /// Reads and decompresses the `mtree` entry of an installed package.
+    pub fn read_mtree(&self, name: &str, version: &str) -> Result<Vec<MtreeEntry>, AppError> {
+        let file = fs::File::open(self.entry_dir(name, version).join("mtree"))?;
+        let mut content = String::new();
+        GzDecoder::new(file).read_to_string(&mut content)?;
+        parse_mtree(&content)
+    }
+}
+
+/// Parses the contents of a `files` entry.
+pub fn parse_files(content: &str) -> PackageFiles {
+    let mut files = PackageFiles::default();
+    for (key, values) in parse_sections(content) {
+        match key {
+            "FILES" => files.files = values.iter().map(|v| v.to_string()).collect(),
+            "BACKUP" => {
+                files.backup = values
+                    .iter()
+                    .filter_map(|v| v.split_once('\t'))
+                    .map(|(path, hash)| BackupFile {
+                        path: path.to_string(),
+                        hash: hash.to_string(),
+                    })
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+    files
+}
+
+/// Parses the (decompressed) contents of an `mtree` entry.
+///
+/// Only the subset of the mtree format written by makepkg is supported:
+/// `/set` and `/unset` defaults followed by one line per path.
+pub fn parse_mtree(content: &str) -> Result<Vec<MtreeEntry>, AppError> {
+    let mut defaults: HashMap<String, String> = HashMap::new();
+    let mut entries = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let Some(first) = words.next() else {
+            continue;
+        };
+
+        match first {
+            "/set" => {
+                for word in words {
+                    if let Some((key, value)) = word.split_once('=') {
+                        defaults.insert(key.to_string(), value.to_string());
+                    }
+                }
+            }
+            "/unset" => {
+                for word in words {
+                    defaults.remove(word);
+                }
+            }
+            path => {
+                let path = unescape_mtree_path(path);
+                let path = path.strip_prefix("./").unwrap_or(&path).to_string();
+                // Package metadata such as .PKGINFO and .BUILDINFO is not installed
+                if path.starts_with('.') {
+                    continue;
+                }
+
+                let mut keywords = defaults.clone();
+                for word in words {
+                    if let Some((key, value)) = word.split_once('=') {
+                        keywords.insert(key.to_string(), value.to_string());
+                    }
+                }
+                entries.push(mtree_entry(path, &keywords)?);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn mtree_entry(path: String, keywords: &HashMap<String, String>) -> Result<MtreeEntry, AppError> {
+    let invalid = |key: &str, value: &str| {
+        AppError::ParseError(format!("Invalid mtree {} '{}' for {}", key, value, path))
+    };
+
+    let kind = match keywords.get("type").map(String::as_str) {
+        Some("dir") => MtreeKind::Dir,
+        Some("link") => MtreeKind::Link,
+        _ => MtreeKind::File,
+    };
+    let mode = match keywords.get("mode") {
+        Some(v) => Some(u32::from_str_radix(v, 8).map_err(|_| invalid("mode", v))?),
+        None => None,
+    };
+    let number = |key: &str| -> Result<Option<u64>, AppError> {
+        match keywords.get(key) {
+            Some(v) => v.parse().map(Some).map_err(|_| invalid(key, v)),
+            None => Ok(None),
+        }
+    };
+
+    Ok(MtreeEntry {
+        kind,
+        mode,
+        uid: number("uid")?.map(|v| v as u32),
+        gid: number("gid")?.map(|v| v as u32),
+        size: number("size")?,
+        md5: keywords.get("md5digest").cloned(),
+        sha256: keywords.get("sha256digest").cloned(),
+        link: keywords.get("link").map(|l| unescape_mtree_path(l)),
+        path,
+    })
+}
+
+/// Decodes the `\ooo` octal escapes mtree uses for special characters.
+fn unescape_mtree_path(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\'
+            && i + 3 < bytes.len()
+            && bytes[i + 1..i + 4].iter().all(|b| (b'0'..=b'7').contains(b))
+        {
+            let value = bytes[i + 1..i + 4]
+                .iter()
+                .fold(0u32, |acc, b| acc * 8 + u32::from(b - b'0'));
+            out.push(value as u8);
+            i += 4;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_db() -> LocalDb {
+        LocalDb::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/example/db"))
+    }
+
+    #[test]
+    fn reads_local_database_and_skips_broken_entries() {
+        let mut warnings = Vec::new();
+        let mut packages = fixture_db().read_packages(&mut warnings).unwrap();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["bash", "glibc"]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("broken-1.0-1"));
+
+        let bash = &packages[0];
+        assert_eq!(bash.version, "5.2.037-1");
+        assert_eq!(bash.install_date, Some(1727000000));
+        assert_eq!(bash.installed_size, Some(9437184));
+        assert_eq!(bash.reason, None);
+        assert_eq!(bash.depends, ["readline", "glibc", "ncurses"]);
+        assert_eq!(bash.optdepends, ["bash-completion: for tab completion"]);
+        assert_eq!(bash.provides, ["sh"]);
+
+        let glibc = &packages[1];
+        assert_eq!(glibc.install_date, None);
+        assert_eq!(glibc.reason, Some(1));
+    }
+
+    #[test]
+    fn reads_files_entry() {
+        let files = fixture_db().read_files("bash", "5.2.037-1").unwrap();
+        assert_eq!(files.files.len(), 6);
+        assert!(files.files.contains(&"usr/bin/".to_string()));
+        assert_eq!(files.backup.len(), 1);
+        assert_eq!(files.backup[0].path, "etc/bash.bashrc");
+        assert_eq!(files.backup[0].hash, "027d7f7c4e4e2b8e9a1e5b5ee8f7c3a1");
+    }
+}
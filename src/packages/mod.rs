@@ -1,3 +1,25 @@
+pub mod archive;
 pub mod aur;
+pub mod aur_cache;
+pub mod cache;
+pub mod config_drift;
+pub mod deps;
+pub mod desc;
+pub mod downgrade;
+pub mod files;
+pub mod fixture;
+pub mod integrity;
+pub mod local_db;
 pub mod models;
-pub mod pacman;
\ No newline at end of file
+pub mod modified_configs;
+pub mod orphans;
+pub mod pacman;
+pub mod pacman_conf;
+pub mod pacman_log;
+pub mod pkginfo;
+pub mod removal;
+pub mod source;
+pub mod sync_db;
+pub mod time_travel;
+pub mod unowned;
+pub mod vercmp;
\ No newline at end of file
@@ -1,6 +1,10 @@
-use crate::error::AppError;
+use crate::{
+    error::AppError,
+    packages::{aur::AurPackage, integrity::IntegrityReport, vercmp::vercmp},
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fmt;
 use std::str::FromStr;
 
@@ -10,35 +14,116 @@ pub struct Package {
     pub version: String,
     pub description: String,
     pub repository: Repository,
-    pub install_date: DateTime<Utc>,
+    /// `None` for available packages that are not installed
+    pub install_date: Option<DateTime<Utc>>,
     pub build_date: DateTime<Utc>,
+    /// Installed size in MiB
     pub size: f64,
+    /// Download size in MiB, only known for packages read from a sync database
+    pub download_size: Option<f64>,
+    /// Version in the sync repository the package belongs to
+    #[serde(default)]
+    pub sync_version: Option<String>,
     pub is_explicit: bool,
+    pub is_installed: bool,
+    #[serde(default)]
+    pub depends: Vec<String>,
+    #[serde(default)]
+    pub optional_deps: Vec<String>,
+    #[serde(default)]
+    pub provides: Vec<String>,
     #[serde(default)]
     pub tags: Vec<String>,
-    pub popularity: Option<f64>,
-    pub num_votes: Option<u32>,
+    /// Metadata from the AUR, for packages published there
+    #[serde(default)]
+    pub aur: Option<AurPackage>,
+    /// When the AUR data was fetched, if it is past its TTL and could not be refreshed
+    #[serde(default)]
+    pub aur_stale_since: Option<DateTime<Utc>>,
+    /// Result of the last integrity check, if one ran
+    #[serde(skip)]
+    pub integrity: Option<Result<IntegrityReport, String>>,
+}
+
+impl Package {
+    /// A package of which only the name is known, e.g. one that is neither
+    /// installed nor in a sync database anymore
+    pub fn unknown(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: String::new(),
+            description: String::new(),
+            repository: Repository::Foreign,
+            install_date: None,
+            build_date: DateTime::default(),
+            size: 0.0,
+            download_size: None,
+            sync_version: None,
+            is_explicit: false,
+            is_installed: false,
+            depends: Vec::new(),
+            optional_deps: Vec::new(),
+            provides: Vec::new(),
+            tags: Vec::new(),
+            aur: None,
+            aur_stale_since: None,
+            integrity: None,
+        }
+    }
+
+    /// An installed package with the given raw dependency strings, for tests
+    #[cfg(test)]
+    pub(crate) fn installed(name: &str, explicit: bool, depends: &[&str]) -> Self {
+        Self {
+            version: "1.0-1".to_string(),
+            is_explicit: explicit,
+            is_installed: true,
+            depends: depends.iter().map(|d| d.to_string()).collect(),
+            ..Self::unknown(name)
+        }
+    }
+
+    /// The sync repository version, if it is newer than the installed one
+    pub fn repo_update(&self) -> Option<&str> {
+        let sync_version = self.sync_version.as_deref()?;
+        (self.is_installed && vercmp(&self.version, sync_version) == Ordering::Less)
+            .then_some(sync_version)
+    }
+
+    /// The AUR version, if it is newer than the installed one
+    pub fn aur_update(&self) -> Option<&str> {
+        let aur = self.aur.as_ref()?;
+        (self.is_installed && vercmp(&self.version, &aur.version) == Ordering::Less)
+            .then_some(aur.version.as_str())
+    }
+
+    /// The newer version from the sync repositories or the AUR, if any
+    pub fn available_update(&self) -> Option<&str> {
+        self.repo_update().or_else(|| self.aur_update())
+    }
+
+    /// Whether the last integrity check found problems with the package's files
+    pub fn has_integrity_problems(&self) -> bool {
+        matches!(&self.integrity, Some(Ok(report)) if !report.is_ok())
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Repository {
-    Core,
-    Extra,
-    Multilib,
-    Community,
-    AUR,
-    Unknown,
+    /// A sync repository, named as in pacman.conf
+    Sync(String),
+    /// Installed, but not available from any sync repository
+    Foreign,
+    /// A foreign package that is published on the AUR
+    Aur,
 }
 
-impl From<&str> for Repository {
-    fn from(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "core" => Self::Core,
-            "extra" => Self::Extra,
-            "multilib" => Self::Multilib,
-            "community" => Self::Community,
-            "aur" | "local" => Self::AUR,
-            _ => Self::AUR,
+impl fmt::Display for Repository {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Repository::Sync(name) => write!(f, "{}", name),
+            Repository::Foreign => write!(f, "foreign"),
+            Repository::Aur => write!(f, "aur"),
         }
     }
 }
@@ -50,6 +135,8 @@ pub enum SortKey {
     InstallDate,
     UpdateDate,
     Popularity,
+    Votes,
+    LastModified,
 }
 
 impl fmt::Display for SortKey {
@@ -60,6 +147,8 @@ impl fmt::Display for SortKey {
             SortKey::InstallDate => write!(f, "Installed Date"),
             SortKey::UpdateDate => write!(f, "Update Date"),
             SortKey::Popularity => write!(f, "Popularity"),
+            SortKey::Votes => write!(f, "AUR Votes"),
+            SortKey::LastModified => write!(f, "AUR Last Modified"),
         }
     }
 }
@@ -73,6 +162,8 @@ impl FromStr for SortKey {
             "installed" => Ok(Self::InstallDate),
             "updated" => Ok(Self::UpdateDate),
             "popularity" => Ok(Self::Popularity),
+            "votes" => Ok(Self::Votes),
+            "modified" => Ok(Self::LastModified),
             _ => Err(AppError::InvalidInput(format!("Invalid sort key: {}", s))),
         }
     }
@@ -83,7 +174,28 @@ pub enum ShowMode {
     ExplicitlyInstalled,
     Dependencies,
     Orphans,
+    /// Explicitly installed packages that no installed package requires
+    Leaves,
+    /// Explicitly installed packages that another explicitly installed
+    /// package requires anyway
+    RedundantExplicit,
     AllAvailable, 
+    /// AUR packages that are orphaned or flagged out of date
+    AurNeedsAttention,
+    /// AUR packages with a newer version than the installed one
+    AurUpdates,
+    /// Installed packages with a newer version in the sync databases
+    RepoUpgrades,
+    /// The packages installed at a past moment, see `TimeTravel`
+    TimeTravel,
+    /// Packages with files in the package cache, see `PackageCache`
+    PackageCache,
+    /// Packages with `.pacnew` or `.pacsave` files, see `ConfigDrift`
+    ConfigDrift,
+    /// Packages with edited or deleted backup files, see `ModifiedConfigs`
+    ModifiedConfigs,
+    /// Installed packages whose files fail the integrity check
+    IntegrityProblems,
 }
 
 impl fmt::Display for ShowMode {
@@ -93,7 +205,17 @@ impl fmt::Display for ShowMode {
             ShowMode::ExplicitlyInstalled => write!(f, "Explicitly Installed"),
             ShowMode::Dependencies => write!(f, "Dependencies"),
             ShowMode::Orphans => write!(f, "Orphans"),
+            ShowMode::Leaves => write!(f, "Leaves"),
+            ShowMode::RedundantExplicit => write!(f, "Redundant Explicit"),
             ShowMode::AllAvailable => write!(f, "All Available"), // --- ADDED ---
+            ShowMode::AurNeedsAttention => write!(f, "AUR Orphaned/Out of Date"),
+            ShowMode::AurUpdates => write!(f, "AUR Updates Available"),
+            ShowMode::RepoUpgrades => write!(f, "Pending Upgrades"),
+            ShowMode::TimeTravel => write!(f, "Time Travel"),
+            ShowMode::PackageCache => write!(f, "Package Cache"),
+            ShowMode::ConfigDrift => write!(f, "Config Drift"),
+            ShowMode::ModifiedConfigs => write!(f, "Modified Configs"),
+            ShowMode::IntegrityProblems => write!(f, "Integrity Problems"),
         }
     }
 }
\ No newline at end of file
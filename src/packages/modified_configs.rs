@@ -0,0 +1,209 @@
+use crate::packages::{integrity::file_digest, models::Package, source::PackageSource};
+use md5::Md5;
+use std::{collections::BTreeMap, fmt, io, path::Path};
+
+/// How a backup file differs from the version its package shipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigStatus {
+    /// The contents were edited
+    Modified,
+    /// The file was deleted
+    Missing,
+    /// The file could not be read, e.g. for lack of permissions
+    Unreadable(String),
+}
+
+impl fmt::Display for ConfigStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigStatus::Modified => write!(f, "modified"),
+            ConfigStatus::Missing => write!(f, "missing"),
+            ConfigStatus::Unreadable(e) => write!(f, "unreadable: {}", e),
+        }
+    }
+}
+
+/// A backup file that does not match its package's checksum.
+#[derive(Debug, Clone)]
+pub struct ModifiedConfig {
+    /// Path relative to the installation root
+    pub path: String,
+    pub status: ConfigStatus,
+}
+
+/// Backup files (the `backup=` array of a PKGBUILD, usually configuration)
+/// that differ from what their package shipped, like `pacman -Qii` marks
+/// as `[modified]`.
+///
+/// The local database records the MD5 checksum of every backup file in
+/// the package's `files` entry.
+#[derive(Debug, Clone, Default)]
+pub struct ModifiedConfigs {
+    /// Package name -> its differing backup files, sorted by path
+    files: BTreeMap<String, Vec<ModifiedConfig>>,
+}
+
+impl ModifiedConfigs {
+    /// Checks the backup files of all installed packages. Packages whose
+    /// file list cannot be read are skipped.
+    pub fn scan(source: &dyn PackageSource, packages: &[Package]) -> Self {
+        let root = source.root();
+        let mut files = BTreeMap::new();
+        for pkg in packages.iter().filter(|p| p.is_installed) {
+            let Ok(package_files) = source.package_files(&pkg.name, &pkg.version) else {
+                continue;
+            };
+            let mut modified: Vec<ModifiedConfig> = package_files
+                .backup
+                .into_iter()
+                .filter_map(|backup| {
+                    let status = check(&root.join(&backup.path), &backup.hash)?;
+                    Some(ModifiedConfig {
+                        path: backup.path,
+                        status,
+                    })
+                })
+                .collect();
+            if !modified.is_empty() {
+                modified.sort_by(|a, b| a.path.cmp(&b.path));
+                files.insert(pkg.name.clone(), modified);
+            }
+        }
+        Self { files }
+    }
+
+    /// Checks one backup file of a package again, e.g. after it was replaced,
+    /// without rescanning everything.
+    pub fn recheck(&mut self, source: &dyn PackageSource, pkg: &Package, path: &str) {
+        let Ok(package_files) = source.package_files(&pkg.name, &pkg.version) else {
+            return;
+        };
+        let Some(backup) = package_files.backup.into_iter().find(|b| b.path == path) else {
+            return;
+        };
+        let status = check(&source.root().join(path), &backup.hash);
+        let modified = self.files.entry(pkg.name.clone()).or_default();
+        modified.retain(|f| f.path != path);
+        if let Some(status) = status {
+            modified.push(ModifiedConfig {
+                path: backup.path,
+                status,
+            });
+            modified.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+        if modified.is_empty() {
+            self.files.remove(&pkg.name);
+        }
+    }
+
+    /// Differing backup files of a package.
+    pub fn for_package(&self, name: &str) -> &[ModifiedConfig] {
+        self.files.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether the package has backup files that were edited or deleted.
+    /// Files that could not be read do not count.
+    pub fn is_modified(&self, name: &str) -> bool {
+        self.for_package(name)
+            .iter()
+            .any(|f| !matches!(f.status, ConfigStatus::Unreadable(_)))
+    }
+}
+
+// `None` if the file matches the checksum
+fn check(path: &Path, hash: &str) -> Option<ConfigStatus> {
+    let digest = match file_digest::<Md5>(path) {
+        Ok(digest) => digest,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Some(ConfigStatus::Missing),
+        Err(e) => return Some(ConfigStatus::Unreadable(e.to_string())),
+    };
+    (!digest.eq_ignore_ascii_case(hash)).then_some(ConfigStatus::Modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::fixture::tests::{example, example_packages};
+    use std::fs;
+
+    const EMPTY_MD5: &str = "d41d8cd98f00b204e9800998ecf8427e";
+
+    fn statuses(configs: &ModifiedConfigs, name: &str) -> Vec<(String, ConfigStatus)> {
+        configs
+            .for_package(name)
+            .iter()
+            .map(|f| (f.path.clone(), f.status.clone()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn scan_finds_modified_and_missing_backup_files() {
+        let configs = ModifiedConfigs::scan(&example(), &example_packages().await);
+
+        assert_eq!(
+            statuses(&configs, "bash"),
+            [("etc/bash.bashrc".to_string(), ConfigStatus::Modified)]
+        );
+        // company-tools.conf matches its checksum
+        assert_eq!(
+            statuses(&configs, "company-tools"),
+            [("etc/company-tools.d/defaults.conf".to_string(), ConfigStatus::Missing)]
+        );
+        assert!(configs.for_package("firefox").is_empty());
+        assert!(configs.is_modified("bash"));
+        assert!(!configs.is_modified("firefox"));
+    }
+
+    #[test]
+    fn checks_files_against_their_checksum() {
+        let root = tempfile::tempdir().unwrap();
+        let file = root.path().join("empty.conf");
+        fs::write(&file, "").unwrap();
+
+        assert_eq!(check(&file, EMPTY_MD5), None);
+        assert_eq!(check(&file, &EMPTY_MD5.to_uppercase()), None);
+        fs::write(&file, "edited\n").unwrap();
+        assert_eq!(check(&file, EMPTY_MD5), Some(ConfigStatus::Modified));
+        assert_eq!(check(&root.path().join("gone.conf"), EMPTY_MD5), Some(ConfigStatus::Missing));
+        // A directory where the file should be cannot be read
+        assert!(matches!(check(root.path(), EMPTY_MD5), Some(ConfigStatus::Unreadable(_))));
+    }
+
+    #[test]
+    fn unreadable_files_do_not_count_as_modified() {
+        let configs = ModifiedConfigs {
+            files: BTreeMap::from([(
+                "app".to_string(),
+                vec![ModifiedConfig {
+                    path: "etc/app.conf".to_string(),
+                    status: ConfigStatus::Unreadable("Permission denied".to_string()),
+                }],
+            )]),
+        };
+
+        assert_eq!(configs.for_package("app").len(), 1);
+        assert!(!configs.is_modified("app"));
+    }
+
+    #[tokio::test]
+    async fn recheck_replaces_the_stale_status() {
+        let source = example();
+        let packages = example_packages().await;
+        let company_tools = packages.iter().find(|p| p.name == "company-tools").unwrap();
+        let mut configs = ModifiedConfigs::scan(&source, &packages);
+        configs.files.get_mut("company-tools").unwrap().push(ModifiedConfig {
+            path: "etc/company-tools.conf".to_string(),
+            status: ConfigStatus::Modified,
+        });
+
+        configs.recheck(&source, company_tools, "etc/company-tools.conf");
+        assert_eq!(
+            statuses(&configs, "company-tools"),
+            [("etc/company-tools.d/defaults.conf".to_string(), ConfigStatus::Missing)]
+        );
+
+        // Paths that are not backup files of the package are ignored
+        configs.recheck(&source, company_tools, "usr/bin/ctool");
+        assert_eq!(configs.for_package("company-tools").len(), 1);
+    }
+}
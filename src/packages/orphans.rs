@@ -0,0 +1,193 @@
+use crate::packages::{deps::DependencyGraph, models::Package};
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt,
+};
+
+/// Why a package installed as a dependency counts as orphaned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrphanReason {
+    /// Nothing requires it, what `pacman -Qdt` reports
+    Unrequired,
+    /// Only listed as an optional dependency, what `pacman -Qdtt` adds
+    OptionalOnly { by: Vec<String> },
+    /// Only required by other orphans, which `-Qdt` reports once those are gone
+    RequiredByOrphans { by: Vec<String> },
+    /// Part of a group of dependencies that only require each other
+    Cycle { with: Vec<String> },
+}
+
+impl fmt::Display for OrphanReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrphanReason::Unrequired => write!(f, "not required"),
+            OrphanReason::OptionalOnly { by } => write!(f, "only optional for {}", by.join(", ")),
+            OrphanReason::RequiredByOrphans { by } => {
+                write!(f, "only required by orphans {}", by.join(", "))
+            }
+            OrphanReason::Cycle { with } => write!(f, "dependency cycle with {}", with.join(", ")),
+        }
+    }
+}
+
+/// Packages installed as dependencies that no explicitly installed package
+/// needs, directly or through other dependencies.
+///
+/// Unlike `pacman -Qdt`, this covers everything a repeated
+/// `pacman -Rns $(pacman -Qdtq)` would eventually remove in one pass.
+/// Optional dependencies do not keep a package installed.
+#[derive(Debug, Clone, Default)]
+pub struct Orphans {
+    reasons: BTreeMap<String, OrphanReason>,
+}
+
+impl Orphans {
+    pub fn find(packages: &[Package], graph: &DependencyGraph) -> Self {
+        // Everything reachable from an explicit package is needed
+        let mut needed: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<&str> = packages
+            .iter()
+            .filter(|p| p.is_explicit)
+            .map(|p| p.name.as_str())
+            .collect();
+        while let Some(name) = queue.pop_front() {
+            if needed.insert(name) {
+                queue.extend(graph.depends_on(name).iter().map(String::as_str));
+            }
+        }
+
+        let reasons = packages
+            .iter()
+            .filter(|p| !needed.contains(p.name.as_str()))
+            .map(|p| (p.name.clone(), orphan_reason(&p.name, graph)))
+            .collect();
+        Self { reasons }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.reasons.contains_key(name)
+    }
+
+    pub fn reason(&self, name: &str) -> Option<&OrphanReason> {
+        self.reasons.get(name)
+    }
+
+    /// Orphan names in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.reasons.keys().map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reasons.is_empty()
+    }
+}
+
+// Everything requiring an orphan is an orphan too, otherwise the orphan
+// would be reachable from an explicit package
+fn orphan_reason(name: &str, graph: &DependencyGraph) -> OrphanReason {
+    let required_by = graph.required_by(name);
+    if required_by.is_empty() {
+        let optional_for = graph.optional_for(name);
+        return if optional_for.is_empty() {
+            OrphanReason::Unrequired
+        } else {
+            OrphanReason::OptionalOnly { by: optional_for.to_vec() }
+        };
+    }
+
+    let dependents = graph.all_dependents(name);
+    let cycle: Vec<String> = graph
+        .all_dependencies(name)
+        .into_iter()
+        .filter(|dep| dependents.contains(dep))
+        .collect();
+    if cycle.is_empty() {
+        OrphanReason::RequiredByOrphans { by: required_by.to_vec() }
+    } else {
+        OrphanReason::Cycle { with: cycle }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(packages: &[Package]) -> Orphans {
+        Orphans::find(packages, &DependencyGraph::build(packages))
+    }
+
+    #[test]
+    fn finds_dependency_cycles_without_an_explicit_root() {
+        let packages = [
+            Package::installed("app", true, &["lib"]),
+            Package::installed("lib", false, &[]),
+            Package::installed("setuptools", false, &["packaging"]),
+            Package::installed("packaging", false, &["setuptools"]),
+            Package::installed("oldlib", false, &["six"]),
+            Package::installed("six", false, &[]),
+        ];
+        let orphans = find(&packages);
+        assert_eq!(orphans.names().collect::<Vec<_>>(), ["oldlib", "packaging", "setuptools", "six"]);
+        assert_eq!(
+            orphans.reason("setuptools"),
+            Some(&OrphanReason::Cycle { with: vec!["packaging".to_string()] })
+        );
+        assert_eq!(orphans.reason("oldlib"), Some(&OrphanReason::Unrequired));
+        assert_eq!(
+            orphans.reason("six"),
+            Some(&OrphanReason::RequiredByOrphans { by: vec!["oldlib".to_string()] })
+        );
+        assert!(!orphans.contains("lib"));
+    }
+
+    #[test]
+    fn cycles_reachable_from_an_explicit_package_are_needed() {
+        let packages = [
+            Package::installed("app", true, &["a"]),
+            Package::installed("a", false, &["b"]),
+            Package::installed("b", false, &["a"]),
+        ];
+        assert!(find(&packages).is_empty());
+    }
+
+    #[test]
+    fn optional_dependencies_do_not_keep_a_package() {
+        let mut firefox = Package::installed("firefox", true, &["gtk3"]);
+        firefox.optional_deps = vec!["libnotify: Notification integration".to_string()];
+        let packages = [
+            firefox,
+            Package::installed("gtk3", false, &[]),
+            Package::installed("libnotify", false, &["gtk3"]),
+        ];
+        let orphans = find(&packages);
+        assert_eq!(orphans.names().collect::<Vec<_>>(), ["libnotify"]);
+        assert_eq!(
+            orphans.reason("libnotify"),
+            Some(&OrphanReason::OptionalOnly { by: vec!["firefox".to_string()] })
+        );
+    }
+
+    #[test]
+    fn providers_of_needed_names_are_needed() {
+        let mut bash = Package::installed("bash", false, &[]);
+        bash.provides = vec!["sh=5.2".to_string()];
+        let mut dash = Package::installed("dash", false, &[]);
+        dash.provides = vec!["sh".to_string()];
+        let packages = [Package::installed("script", true, &["sh>=5"]), bash, dash];
+        // Every provider counts, as the graph does not pick one
+        assert!(find(&packages).is_empty());
+
+        let packages = [Package::installed("script", true, &[]), packages[1].clone()];
+        assert_eq!(find(&packages).names().collect::<Vec<_>>(), ["bash"]);
+    }
+
+    #[tokio::test]
+    async fn finds_orphans_on_the_example_system() {
+        let packages = crate::packages::fixture::tests::example_packages().await;
+        let orphans = find(&packages);
+        assert_eq!(
+            orphans.names().collect::<Vec<_>>(),
+            ["libnotify", "python-oldlib", "python-packaging", "python-setuptools", "python-six"]
+        );
+    }
+}
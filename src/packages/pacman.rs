@@ -1,257 +1,370 @@
 use crate::{
+    config::{AurConfig, PacmanConfig},
     db,
     error::AppError,
     packages::{
-        aur::fetch_aur_package_data,
+        aur::{AurClient, AurLookup},
+        aur_cache::AurCache,
+        desc::Desc,
+        local_db::{LocalDb, MtreeEntry, PackageFiles},
         models::{Package, Repository},
+        pacman_conf::PacmanConf,
+        pacman_log::PacmanLog,
+        source::{BoxFuture, PackageSource},
+        sync_db::{SyncDb, SyncPackage},
     },
 };
-use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
-use std::collections::HashMap;
-use std::process::Command;
-
-// Main function to get all installed packages
-pub async fn get_all_packages() -> Result<Vec<Package>, AppError> {
-    // Build a map of package names to their repositories for faster lookup
-    let repo_map = build_repo_map()?;
-
-    let output = Command::new("pacman")
-        .arg("-Qi")
-        .env("LC_ALL", "C") // Set locale to C to ensure consistent output format
-        .output()
-        .map_err(|e| AppError::CommandFailed(format!("Failed to execute pacman: {}", e)))?;
-
-    if !output.status.success() {
-        return Err(AppError::CommandFailed(
-            "pacman -Qi command failed".to_string(),
-        ));
-    }
-
-    let output_str = String::from_utf8(output.stdout)
-        .map_err(|_| AppError::ParseError("Pacman output is not valid UTF-8".to_string()))?;
-
-    // Load custom tags from our DB
-    let tags_db = db::load_tags()?;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-    // Each package info block is separated by a double newline
-    let mut packages: Vec<Package> = output_str
-        .trim()
-        .split("\n\n")
-        .filter_map(|block| parse_package_block(block, &tags_db, &repo_map).ok())
-        .collect();
+/// Package source backed by the live pacman databases and the AUR.
+pub struct PacmanSource {
+    config: PacmanConfig,
+    aur_config: AurConfig,
+}
 
-    // Get AUR packages and fetch their popularity data
-    let aur_package_names: Vec<String> = packages
-        .iter()
-        .filter(|p| p.repository == Repository::AUR)
-        .map(|p| p.name.clone())
-        .collect();
+impl PacmanSource {
+    pub fn new(config: PacmanConfig, aur_config: AurConfig) -> Self {
+        Self { config, aur_config }
+    }
 
-    if !aur_package_names.is_empty() {
-        let aur_data = fetch_aur_package_data(aur_package_names).await?;
-        for pkg in &mut packages {
-            if pkg.repository == Repository::AUR {
-                if let Some(aur_pkg) = aur_data.get(&pkg.name) {
-                    pkg.popularity = Some(aur_pkg.popularity);
-                    pkg.num_votes = Some(aur_pkg.num_votes);
+    // Serves AUR data from the on-disk cache and only fetches expired entries.
+    // If the AUR cannot be reached, expired entries are returned marked as stale.
+    async fn cached_aur_packages(&self, package_names: Vec<String>) -> Result<AurLookup, AppError> {
+        let ttl = Duration::hours(self.aur_config.cache_ttl_hours as i64);
+        let now = Utc::now();
+        let mut cache = AurCache::load();
+        let mut warnings = Vec::new();
+
+        let expired = cache.expired(&package_names, ttl, now);
+        if !expired.is_empty() {
+            let fetched = AurClient::from_config(&self.aur_config).fetch(expired.clone()).await;
+            let succeeded: Vec<String> = expired
+                .into_iter()
+                .filter(|name| !fetched.failed.contains_key(name))
+                .collect();
+            if !succeeded.is_empty() {
+                cache.update(&succeeded, fetched.packages, now);
+                // The fetched data is still good even if it cannot be kept
+                if let Err(e) = cache.save() {
+                    warnings.push(format!("Failed to save the AUR cache: {}", e));
                 }
             }
+            if !fetched.failed.is_empty() {
+                warnings.push(describe_aur_failures(&fetched.failed));
+            }
         }
+
+        Ok(AurLookup {
+            packages: cache.lookup(&package_names, ttl, now),
+            warnings,
+        })
     }
 
-    Ok(packages)
+    // pacman run through the privilege command against the configured system,
+    // so it acts on the same root, database and pacman.conf the TUI shows
+    fn pacman_command(&self) -> Vec<String> {
+        let defaults = PacmanConfig::default();
+        let mut command = self.config.privilege_command.clone();
+        command.extend([
+            "pacman".to_string(),
+            "--dbpath".to_string(),
+            self.config.db_path.to_string_lossy().into_owned(),
+        ]);
+        if self.config.root != defaults.root {
+            command.extend(["--root".to_string(), self.config.root.to_string_lossy().into_owned()]);
+        }
+        if self.config.config_file != defaults.config_file {
+            command.extend([
+                "--config".to_string(),
+                self.config.config_file.to_string_lossy().into_owned(),
+            ]);
+        }
+        command
+    }
 }
 
-/// Gets all available packages from repositories using `pacman -Sl`
-pub fn get_all_available_packages() -> Result<Vec<Package>, AppError> {
-    let output = Command::new("pacman")
-        .arg("-Sl")
-        .env("LC_ALL", "C")
-        .output()
-        .map_err(|e| AppError::CommandFailed(format!("Failed to execute pacman -Sl: {}", e)))?;
-
-    if !output.status.success() {
-        return Err(AppError::CommandFailed(
-            "pacman -Sl command failed".to_string(),
-        ));
+impl PackageSource for PacmanSource {
+    fn local_packages(&self, warnings: &mut Vec<String>) -> Result<Vec<Desc>, AppError> {
+        LocalDb::from_config(&self.config).read_packages(warnings)
     }
 
-    let output_str = String::from_utf8(output.stdout)
-        .map_err(|_| AppError::ParseError("pacman -Sl output is not valid UTF-8".to_string()))?;
-
-    let mut packages = Vec::new();
-    let now = Utc::now(); // For dummy dates
-
-    for line in output_str.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        // Format: <repo> <name> <version> [installed_version]
-        if parts.len() >= 3 {
-            let repo = parts[0];
-            let name = parts[1];
-            let version = parts[2];
-
-            let pkg = Package {
-                name: name.to_string(),
-                version: version.to_string(),
-                description: "N/A (available package)".to_string(), // -Sl doesn't provide description
-                repository: Repository::from(repo),
-                install_date: now, // Dummy data
-                build_date: now,   // Dummy data
-                size: 0.0,         // Dummy data
-                is_explicit: false, // Dummy data
-                tags: Vec::new(),  // Not applicable
-                popularity: None,  // Not fetched for this view
-                num_votes: None,
-            };
-            packages.push(pkg);
+    // Reads every package from the sync databases, in pacman.conf repository order
+    fn sync_packages(&self, warnings: &mut Vec<String>) -> Result<Vec<SyncPackage>, AppError> {
+        let sync_db = SyncDb::from_config(&self.config);
+        match PacmanConf::load(&self.config.config_file) {
+            Ok(conf) => sync_db.read_repos(&conf.repositories, warnings),
+            // Without a readable pacman.conf, fall back to every database on disk
+            Err(_) => sync_db.read_packages(warnings),
         }
     }
-    Ok(packages)
-}
 
-// Gets a list of orphan package names
-pub fn get_orphan_package_names() -> Result<Vec<String>, AppError> {
-    let output = Command::new("pacman")
-        .arg("-Qdt")
-        .output()
-        .map_err(|e| AppError::CommandFailed(format!("Failed to execute pacman -Qdt: {}", e)))?;
-
-    if !output.status.success() {
-        // -Qdt can return 1 if no packages are found, which is not an error
-        if output.stdout.is_empty() {
-            return Ok(Vec::new());
+    fn root(&self) -> PathBuf {
+        self.config.root.clone()
+    }
+
+    // pacman falls back to its default cache directory if none is configured
+    fn cache_dirs(&self) -> Vec<PathBuf> {
+        match PacmanConf::load(&self.config.config_file) {
+            Ok(conf) if !conf.cache_dirs.is_empty() => conf.cache_dirs,
+            _ => vec![PathBuf::from("/var/cache/pacman/pkg")],
         }
-        return Err(AppError::CommandFailed(
-            "pacman -Qdt command failed".to_string(),
-        ));
     }
 
-    let output_str = String::from_utf8(output.stdout)
-        .map_err(|_| AppError::ParseError("pacman -Qdt output is not valid UTF-8".to_string()))?;
+    fn package_files(&self, name: &str, version: &str) -> Result<PackageFiles, AppError> {
+        LocalDb::from_config(&self.config).read_files(name, version)
+    }
 
-    let names: Vec<String> = output_str
-        .lines()
-        .filter_map(|line| line.split_whitespace().next().map(String::from))
-        .collect();
-    
-    Ok(names)
-}
+    fn package_mtree(&self, name: &str, version: &str) -> Result<Vec<MtreeEntry>, AppError> {
+        LocalDb::from_config(&self.config).read_mtree(name, version)
+    }
 
+    fn pacman_log(&self) -> Result<PacmanLog, AppError> {
+        PacmanLog::load(&self.config.log_file)
+    }
 
-// Builds a HashMap mapping package names to their repository
-fn build_repo_map() -> Result<HashMap<String, String>, AppError> {
-    let output = Command::new("pacman")
-        .arg("-Sl")
-        .output()
-        .map_err(|e| AppError::CommandFailed(format!("Failed to execute pacman -Sl: {}", e)))?;
+    fn install_reason_command(&self, package_names: &[String], explicit: bool) -> Option<Vec<String>> {
+        let mut command = self.pacman_command();
+        command.extend([
+            "-D".to_string(),
+            if explicit { "--asexplicit" } else { "--asdeps" }.to_string(),
+        ]);
+        command.extend(package_names.iter().cloned());
+        Some(command)
+    }
 
-    if !output.status.success() {
-        return Err(AppError::CommandFailed(
-            "pacman -Sl command failed".to_string(),
-        ));
+    fn remove_files_command(&self, paths: &[&Path]) -> Option<Vec<String>> {
+        let mut command = self.config.privilege_command.clone();
+        command.extend(["rm".to_string(), "-f".to_string(), "--".to_string()]);
+        command.extend(paths.iter().map(|p| p.to_string_lossy().into_owned()));
+        Some(command)
     }
 
-    let output_str = String::from_utf8(output.stdout)
-        .map_err(|_| AppError::ParseError("pacman -Sl output is not valid UTF-8".to_string()))?;
+    fn move_file_command(&self, from: &Path, to: &Path) -> Option<Vec<String>> {
+        let mut command = self.config.privilege_command.clone();
+        command.extend(["mv".to_string(), "-f".to_string(), "--".to_string()]);
+        command.extend([from, to].map(|p| p.to_string_lossy().into_owned()));
+        Some(command)
+    }
 
-    let mut repo_map = HashMap::new();
-    for line in output_str.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 3 {
-            let repo = parts[0];
-            let name = parts[1];
-            repo_map.insert(name.to_string(), repo.to_string());
-        }
+    fn merge_files_command(&self, old: &Path, new: &Path) -> Option<Vec<String>> {
+        let mut command = self.config.privilege_command.clone();
+        command.extend(self.config.merge_tool.iter().cloned());
+        command.extend([old, new].map(|p| p.to_string_lossy().into_owned()));
+        Some(command)
+    }
+
+    fn install_files_command(&self, paths: &[&Path]) -> Option<Vec<String>> {
+        let mut command = self.pacman_command();
+        command.push("-U".to_string());
+        command.extend(paths.iter().map(|p| p.to_string_lossy().into_owned()));
+        Some(command)
+    }
+
+    fn aur_packages(&self, package_names: Vec<String>) -> BoxFuture<'_, Result<AurLookup, AppError>> {
+        Box::pin(self.cached_aur_packages(package_names))
     }
-    Ok(repo_map)
 }
 
-// Helper to parse a single package info block from `pacman -Qi`
-fn parse_package_block(
-    block: &str,
-    tags_db: &std::collections::HashMap<String, Vec<String>>,
-    repo_map: &HashMap<String, String>,
-) -> Result<Package, AppError> {
-    let mut fields = std::collections::HashMap::new();
-    for line in block.lines() {
-        if let Some((key, value)) = line.split_once(" : ") {
-            fields.insert(key.trim(), value.trim());
+// Summarizes per-package AUR failures for the output log
+fn describe_aur_failures(failed: &HashMap<String, String>) -> String {
+    const SHOWN: usize = 5;
+    let mut names: Vec<&str> = failed.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    let mut listed = names[..names.len().min(SHOWN)].join(", ");
+    if names.len() > SHOWN {
+        listed.push_str(&format!(" and {} more", names.len() - SHOWN));
+    }
+    // Every package of a failed batch shares its error, so show one of them
+    let error = failed.get(names[0]).map(String::as_str).unwrap_or_default();
+    format!("AUR lookup failed for {}, using cached data: {}", listed, error)
+}
+
+// Main function to get all installed packages, with the custom tags from `db::load_tags`.
+// Problems that only degrade the result, like an unreachable AUR, are pushed to `warnings`.
+pub async fn get_all_packages(
+    source: &dyn PackageSource,
+    sync_packages: &[SyncPackage],
+    tags_db: &db::TagDb,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<Package>, AppError> {
+    // Build a map of package names to their sync entries for faster lookup
+    let repo_map = build_repo_map(sync_packages);
+
+    let mut packages: Vec<Package> = source
+        .local_packages(warnings)?
+        .into_iter()
+        .map(|desc| package_from_local_desc(desc, tags_db, &repo_map))
+        .collect();
+
+    // Look up foreign packages on the AUR and fetch their metadata
+    let foreign_package_names: Vec<String> = packages
+        .iter()
+        .filter(|p| p.repository == Repository::Foreign)
+        .map(|p| p.name.clone())
+        .collect();
+
+    if !foreign_package_names.is_empty() {
+        let aur_data = match source.aur_packages(foreign_package_names).await {
+            Ok(lookup) => {
+                warnings.extend(lookup.warnings);
+                lookup.packages
+            }
+            Err(e) => {
+                warnings.push(format!("Failed to load AUR data: {}", e));
+                HashMap::new()
+            }
+        };
+        for pkg in &mut packages {
+            if pkg.repository == Repository::Foreign
+                && let Some(aur_info) = aur_data.get(&pkg.name)
+            {
+                pkg.repository = Repository::Aur;
+                pkg.aur = Some(aur_info.package.clone());
+                pkg.aur_stale_since = aur_info.stale.then_some(aur_info.fetched_at);
+            }
         }
     }
 
-    let name = fields
-        .get("Name")
-        .ok_or_else(|| AppError::ParseError("Missing Name".to_string()))?
-        .to_string();
-
-    // Try to get the repository from the pacman -Qi output,
-    // otherwise fall back to the repo_map created from pacman -Sl.
-    let repository_str = fields
-        .get("Repository")
-        .map(|s| s.to_string())
-        .or_else(|| repo_map.get(&name).cloned())
-        .unwrap_or_else(|| "Unknown".to_string());
-
-    // Size parsing (e.g., "12.34 MiB" or "12,34 MiB")
-    let size_str = fields.get("Installed Size").unwrap_or(&"0.0 KiB");
-    let sanitized_size_str = size_str.replace(',', ".");
-    let size_parts: Vec<&str> = sanitized_size_str.split_whitespace().collect();
-    let size_val: f64 = size_parts.get(0).unwrap_or(&"0.0").parse().unwrap_or(0.0);
-    let size_unit = size_parts.get(1).unwrap_or(&"MiB");
-    let size_mib = match *size_unit {
-        "GiB" => size_val * 1024.0,
-        "MiB" => size_val,
-        "KiB" => size_val / 1024.0,
-        "B" => size_val / (1024.0 * 1024.0),
-        _ => size_val,
-    };
+    Ok(packages)
+}
 
-    // Date parsing
-    let install_date_str = fields
-        .get("Install Date")
-        .ok_or_else(|| AppError::ParseError("Missing Install Date".to_string()))?;
-    let build_date_str = fields
-        .get("Build Date")
-        .ok_or_else(|| AppError::ParseError("Missing Build Date".to_string()))?;
-
-    let package = Package {
-        name: name.clone(),
-        version: fields.get("Version").unwrap_or(&"").to_string(),
-        description: fields.get("Description").unwrap_or(&"").to_string(),
-        repository: Repository::from(repository_str.as_str()),
-        install_date: parse_pacman_date(install_date_str)?,
-        build_date: parse_pacman_date(build_date_str)?,
-        size: size_mib,
-        is_explicit: fields.get("Install Reason").unwrap_or(&"") == &"Explicitly installed",
-        tags: tags_db.get(&name).cloned().unwrap_or_default(),
-        popularity: None,
-        num_votes: None,
-    };
-    Ok(package)
+/// Builds the list of all packages available in the sync databases
+pub fn get_all_available_packages(
+    sync_packages: &[SyncPackage],
+    installed: &[Package],
+) -> Vec<Package> {
+    let installed: HashMap<&str, &Package> =
+        installed.iter().map(|p| (p.name.as_str(), p)).collect();
+
+    sync_packages
+        .iter()
+        .map(|sync_pkg| {
+            let desc = &sync_pkg.desc;
+            let local = installed.get(desc.name.as_str());
+            Package {
+                name: desc.name.clone(),
+                version: desc.version.clone(),
+                description: desc.description.clone(),
+                repository: Repository::Sync(sync_pkg.repository.clone()),
+                install_date: local.and_then(|p| p.install_date),
+                build_date: timestamp_to_date(desc.build_date.unwrap_or_default()),
+                size: bytes_to_mib(desc.installed_size.unwrap_or_default()),
+                download_size: desc.download_size.map(bytes_to_mib),
+                sync_version: Some(desc.version.clone()),
+                is_explicit: local.is_some_and(|p| p.is_explicit),
+                is_installed: local.is_some(),
+                depends: desc.depends.clone(),
+                optional_deps: desc.optdepends.clone(),
+                provides: desc.provides.clone(),
+                tags: local.map(|p| p.tags.clone()).unwrap_or_default(),
+                aur: None, // Not fetched for this view
+                aur_stale_since: None,
+                integrity: None,
+            }
+        })
+        .collect()
 }
-// Helper to parse pacman's date format
-fn parse_pacman_date(date_str: &str) -> Result<DateTime<Utc>, AppError> {
-    // Attempt to parse the date with a few different formats
-    let formats = [
-        "%a %b %d %H:%M:%S %Y",       // "Wed May 01 21:30:00 2024" (LC_ALL=C format)
-        "%a %d %b %Y %I:%M:%S %p %Z", // "Wed 01 May 2024 09:30:00 PM UTC"
-    ];
-
-    for fmt in formats.iter() {
-        if let Ok(dt) = NaiveDateTime::parse_from_str(date_str, fmt) {
-            return Ok(Utc.from_utc_datetime(&dt));
-        }
+
+// Builds a HashMap mapping package names to their sync database entry.
+// When several repositories carry a package, the first one wins like in pacman.
+fn build_repo_map(sync_packages: &[SyncPackage]) -> HashMap<&str, &SyncPackage> {
+    let mut repo_map = HashMap::new();
+    for sync_pkg in sync_packages {
+        repo_map.entry(sync_pkg.desc.name.as_str()).or_insert(sync_pkg);
     }
+    repo_map
+}
+
+// Helper to build a package from its local database `desc` entry
+fn package_from_local_desc(
+    desc: Desc,
+    tags_db: &HashMap<String, Vec<String>>,
+    repo_map: &HashMap<&str, &SyncPackage>,
+) -> Package {
+    // The local database does not record the repository a package came from,
+    // so look it up in the repo_map created from the sync databases.
+    let sync_pkg = repo_map.get(desc.name.as_str());
+    let repository = match sync_pkg {
+        Some(sync_pkg) => Repository::Sync(sync_pkg.repository.clone()),
+        None => Repository::Foreign,
+    };
 
-    // Fallback for formats with a timezone name, assuming local time
-    if let Ok(dt) = DateTime::parse_from_str(date_str, "%a %d %b %Y %I:%M:%S %p %Z") {
-        return Ok(dt.with_timezone(&Utc));
+    Package {
+        repository,
+        install_date: desc.install_date.map(timestamp_to_date),
+        build_date: timestamp_to_date(desc.build_date.unwrap_or_default()),
+        size: bytes_to_mib(desc.installed_size.unwrap_or_default()),
+        // What upgrading to the sync version would download
+        download_size: sync_pkg.and_then(|p| p.desc.download_size).map(bytes_to_mib),
+        sync_version: sync_pkg.map(|p| p.desc.version.clone()),
+        // A missing %REASON% means the package was explicitly installed
+        is_explicit: desc.reason.unwrap_or(0) == 0,
+        is_installed: true,
+        tags: tags_db.get(&desc.name).cloned().unwrap_or_default(),
+        aur: None,
+        aur_stale_since: None,
+        integrity: None,
+        name: desc.name,
+        version: desc.version,
+        description: desc.description,
+        depends: desc.depends,
+        optional_deps: desc.optdepends,
+        provides: desc.provides,
     }
+}
 
-    // If all parsing fails, print a warning and return the current time as a fallback.
-    eprintln!(
-        "Warning: Failed to parse date '{}'. Using current time as a fallback.",
-        date_str
-    );
-    Ok(Utc::now())
-}
\ No newline at end of file
+// Converts the unix timestamps stored in the pacman databases
+pub(crate) fn timestamp_to_date(timestamp: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(timestamp, 0).single().unwrap_or_default()
+}
+
+pub(crate) fn bytes_to_mib(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_package_without_install_date_has_none() {
+        let desc = Desc {
+            name: "glibc".to_string(),
+            version: "2.40-1".to_string(),
+            reason: Some(1),
+            ..Desc::default()
+        };
+        let pkg = package_from_local_desc(desc, &HashMap::new(), &HashMap::new());
+        assert_eq!(pkg.install_date, None);
+        assert!(!pkg.is_explicit);
+        assert_eq!(pkg.repository, Repository::Foreign);
+    }
+
+    #[test]
+    fn pacman_command_targets_the_configured_system() {
+        let source = PacmanSource::new(PacmanConfig::default(), AurConfig::default());
+        assert_eq!(
+            source.pacman_command(),
+            ["sudo", "pacman", "--dbpath", "/var/lib/pacman"]
+        );
+
+        let config = PacmanConfig {
+            root: PathBuf::from("/mnt"),
+            db_path: PathBuf::from("/mnt/var/lib/pacman"),
+            config_file: PathBuf::from("/mnt/etc/pacman.conf"),
+            ..PacmanConfig::default()
+        };
+        let source = PacmanSource::new(config, AurConfig::default());
+        assert_eq!(
+            source.pacman_command(),
+            [
+                "sudo", "pacman", "--dbpath", "/mnt/var/lib/pacman", "--root", "/mnt", "--config",
+                "/mnt/etc/pacman.conf"
+            ]
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use crate::error::AppError;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The parts of `pacman.conf` this application cares about.
+#[derive(Debug, Clone, Default)]
+pub struct PacmanConf {
+    /// Repository sections in the order pacman searches them
+    pub repositories: Vec<String>,
+    /// `CacheDir` entries of the `[options]` section
+    pub cache_dirs: Vec<PathBuf>,
+}
+
+impl PacmanConf {
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let content = fs::read_to_string(path)?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parses the contents of a `pacman.conf` file.
+    ///
+    /// `Include` directives are not followed: inside repository sections
+    /// they only point at mirror lists, which are not needed here.
+    pub fn parse(content: &str) -> Self {
+        let mut conf = Self::default();
+        let mut in_options = false;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_options = section == "options";
+                if !in_options && !conf.repositories.iter().any(|r| r == section) {
+                    conf.repositories.push(section.to_string());
+                }
+            } else if in_options
+                && let Some((key, value)) = line.split_once('=')
+                && key.trim() == "CacheDir"
+            {
+                // Several directories may be given on one line
+                conf.cache_dirs.extend(value.split_whitespace().map(PathBuf::from));
+            }
+        }
+        conf
+    }
+}
@@ -0,0 +1,328 @@
+use crate::error::AppError;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogAction {
+    Installed,
+    Reinstalled,
+    Upgraded,
+    Downgraded,
+    Removed,
+}
+
+impl fmt::Display for LogAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogAction::Installed => write!(f, "installed"),
+            LogAction::Reinstalled => write!(f, "reinstalled"),
+            LogAction::Upgraded => write!(f, "upgraded"),
+            LogAction::Downgraded => write!(f, "downgraded"),
+            LogAction::Removed => write!(f, "removed"),
+        }
+    }
+}
+
+/// A single package change recorded in `pacman.log`.
+#[derive(Debug, Clone)]
+pub struct LogEvent {
+    pub timestamp: DateTime<Utc>,
+    pub action: LogAction,
+    pub package: String,
+    /// Version before the change; `None` for installs
+    pub old_version: Option<String>,
+    /// Version after the change; `None` for removals
+    pub new_version: Option<String>,
+    /// Index into [`PacmanLog::transactions`]
+    pub transaction: usize,
+}
+
+impl LogEvent {
+    /// The version change, e.g. `1.0-1 -> 1.1-1`
+    pub fn versions(&self) -> String {
+        match (&self.old_version, &self.new_version) {
+            (Some(old), Some(new)) if old != new => format!("{} -> {}", old, new),
+            (_, Some(version)) | (Some(version), None) => version.clone(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// Package events that happened in one pacman run.
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    pub started: DateTime<Utc>,
+    /// Indices into [`PacmanLog::events`]
+    pub events: Vec<usize>,
+}
+
+/// The package history recorded in `pacman.log`, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct PacmanLog {
+    pub events: Vec<LogEvent>,
+    pub transactions: Vec<Transaction>,
+    by_package: HashMap<String, Vec<usize>>,
+}
+
+impl PacmanLog {
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        // The log may contain invalid UTF-8 from scriptlet output
+        let content = fs::read(path)?;
+        Ok(Self::parse(&String::from_utf8_lossy(&content)))
+    }
+
+    /// Parses the contents of a `pacman.log` file.
+    ///
+    /// Events are grouped by the `transaction started` lines pacman writes;
+    /// old logs without them get one transaction per pacman run, which starts
+    /// with a `[PACMAN] Running ...` line.
+    pub fn parse(content: &str) -> Self {
+        let mut log = Self::default();
+        let mut in_transaction = false;
+
+        for line in content.lines() {
+            let Some((timestamp, source, message)) = split_line(line) else {
+                continue;
+            };
+            if source == "PACMAN" {
+                // A new run; the next event starts a transaction if no
+                // marker does
+                if message.starts_with("Running ") {
+                    in_transaction = false;
+                }
+                continue;
+            }
+            match message {
+                "transaction started" => {
+                    log.transactions.push(Transaction { started: timestamp, events: Vec::new() });
+                    in_transaction = true;
+                }
+                "transaction completed" | "transaction failed" | "transaction interrupted" => {
+                    in_transaction = false;
+                }
+                _ => {
+                    let Some(event) = parse_event(timestamp, message) else {
+                        continue;
+                    };
+                    if !in_transaction {
+                        log.transactions.push(Transaction { started: timestamp, events: Vec::new() });
+                        in_transaction = true;
+                    }
+                    let index = log.events.len();
+                    if let Some(transaction) = log.transactions.last_mut() {
+                        transaction.events.push(index);
+                    }
+                    log.by_package.entry(event.package.clone()).or_default().push(index);
+                    log.events.push(event);
+                }
+            }
+        }
+
+        // Transactions that did not change any package are not interesting,
+        // so drop them and point the events at their final transaction index
+        log.transactions.retain(|t| !t.events.is_empty());
+        for (index, transaction) in log.transactions.iter().enumerate() {
+            for &event in &transaction.events {
+                log.events[event].transaction = index;
+            }
+        }
+        log
+    }
+
+    /// Events of one package, oldest first.
+    pub fn history(&self, package: &str) -> impl Iterator<Item = &LogEvent> {
+        self.by_package
+            .get(package)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.events[i])
+    }
+
+    /// The last transaction that started at or before `moment`.
+    pub fn last_transaction_before(&self, moment: DateTime<Utc>) -> Option<usize> {
+        self.transactions.iter().rposition(|t| t.started <= moment)
+    }
+
+    /// Reconstructs the installed package versions right after `transaction`,
+    /// or before the first logged transaction if it is `None`.
+    ///
+    /// Works backwards from `current`, the versions installed today, undoing
+    /// every later event. Packages the log never mentions are assumed to have
+    /// been installed all along, so a rotated log still gives sensible results.
+    pub fn installed_after(
+        &self,
+        transaction: Option<usize>,
+        current: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        let mut installed = current.clone();
+        let first_undone = transaction.map_or(0, |t| t + 1);
+        for transaction in self.transactions.iter().skip(first_undone).rev() {
+            for event in self.transaction_events(transaction).rev() {
+                match &event.old_version {
+                    Some(version) => installed.insert(event.package.clone(), version.clone()),
+                    None => installed.remove(&event.package),
+                };
+            }
+        }
+        installed
+    }
+
+    pub fn transaction_events(
+        &self,
+        transaction: &Transaction,
+    ) -> impl DoubleEndedIterator<Item = &LogEvent> {
+        transaction.events.iter().map(|&i| &self.events[i])
+    }
+}
+
+// Splits `[timestamp] [source] message` lines into their parts; only the
+// `ALPM` and `PACMAN` sources are kept
+fn split_line(line: &str) -> Option<(DateTime<Utc>, &str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (timestamp, rest) = rest.split_once("] ")?;
+    let (source, message) = rest.strip_prefix('[')?.split_once("] ")?;
+    if source != "ALPM" && source != "PACMAN" {
+        return None;
+    }
+    Some((parse_timestamp(timestamp)?, source, message.trim_end()))
+}
+
+// pacman >= 5.1 writes ISO 8601 with an offset, older versions local time without seconds
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S%z") {
+        return Some(date.with_timezone(&Utc));
+    }
+    let naive = NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M").ok()?;
+    Local.from_local_datetime(&naive).earliest().map(|date| date.with_timezone(&Utc))
+}
+
+// Parses `upgraded foo (1.0-1 -> 1.1-1)` and friends
+fn parse_event(timestamp: DateTime<Utc>, message: &str) -> Option<LogEvent> {
+    let (verb, rest) = message.split_once(' ')?;
+    let action = match verb {
+        "installed" => LogAction::Installed,
+        "reinstalled" => LogAction::Reinstalled,
+        "upgraded" => LogAction::Upgraded,
+        "downgraded" => LogAction::Downgraded,
+        "removed" => LogAction::Removed,
+        _ => return None,
+    };
+    let (package, versions) = rest.split_once(" (")?;
+    let versions = versions.strip_suffix(')')?;
+    let (old_version, new_version) = match (action, versions.split_once(" -> ")) {
+        (_, Some((old, new))) => (Some(old.to_string()), Some(new.to_string())),
+        (LogAction::Installed, None) => (None, Some(versions.to_string())),
+        (LogAction::Removed, None) => (Some(versions.to_string()), None),
+        (_, None) => (Some(versions.to_string()), Some(versions.to_string())),
+    };
+    Some(LogEvent {
+        timestamp,
+        action,
+        package: package.to_string(),
+        old_version,
+        new_version,
+        transaction: 0,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const LOG: &str = "\
+[2024-10-01T10:00:00+0000] [PACMAN] Running 'pacman -S foo bar'
+[2024-10-01T10:00:01+0000] [ALPM] transaction started
+[2024-10-01T10:00:02+0000] [ALPM] installed foo (1.0-1)
+[2024-10-01T10:00:02+0000] [ALPM] installed bar (2.0-1)
+[2024-10-01T10:00:03+0000] [ALPM] transaction completed
+[2024-10-02T10:00:01+0000] [ALPM] transaction started
+[2024-10-02T10:00:02+0000] [ALPM] upgraded foo (1.0-1 -> 1.1-1)
+[2024-10-02T10:00:02+0000] [ALPM-SCRIPTLET] upgraded foo (not an event)
+[2024-10-02T10:00:03+0000] [ALPM] transaction completed
+this line is not from pacman
+[2024-10-03T10:00:01+0000] [ALPM] transaction started
+[2024-10-03T10:00:02+0000] [ALPM] removed bar (2.0-1)
+[2024-10-03T10:00:02+0000] [ALPM] installed baz (3.0-1)
+[2024-10-03T10:00:02+0000] [ALPM] upgraded baz (3.0-1
+[2024-10-03T10:00:02+0000] [ALPM] frobnicated baz (3.0-1)
+[2024-10-03T10:00:03+0000] [ALPM] transaction completed
+[2024-10-04T10:00:01+0000] [ALPM] transaction started
+[2024-10-04T10:00:02+0000] [ALPM] transaction completed
+[2024-99-99T10:00:02+0000] [ALPM] installed broken (1.0-1)
+";
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_installs_upgrades_and_removals() {
+        let log = PacmanLog::parse(LOG);
+        // Malformed lines and the empty transaction are skipped
+        assert_eq!(log.events.len(), 5);
+        assert_eq!(log.transactions.len(), 3);
+
+        let install = &log.events[0];
+        assert_eq!(install.action, LogAction::Installed);
+        assert_eq!(install.package, "foo");
+        assert_eq!(install.old_version, None);
+        assert_eq!(install.new_version.as_deref(), Some("1.0-1"));
+        assert_eq!(install.timestamp, at("2024-10-01T10:00:02Z"));
+
+        let upgrade = &log.events[2];
+        assert_eq!(upgrade.action, LogAction::Upgraded);
+        assert_eq!(upgrade.versions(), "1.0-1 -> 1.1-1");
+        assert_eq!(upgrade.transaction, 1);
+
+        let removal = &log.events[3];
+        assert_eq!(removal.action, LogAction::Removed);
+        assert_eq!(removal.old_version.as_deref(), Some("2.0-1"));
+        assert_eq!(removal.new_version, None);
+        assert_eq!(removal.transaction, 2);
+
+        let history: Vec<LogAction> = log.history("foo").map(|e| e.action).collect();
+        assert_eq!(history, [LogAction::Installed, LogAction::Upgraded]);
+        assert_eq!(log.history("unknown").count(), 0);
+    }
+
+    #[test]
+    fn groups_events_without_transaction_markers() {
+        let log = PacmanLog::parse(
+            "[2024-10-01T10:00:00+0000] [ALPM] installed foo (1.0-1)\n\
+             [2024-10-01T10:00:00+0000] [ALPM] installed bar (2.0-1)\n\
+             [2024-10-01T10:05:00+0000] [PACMAN] Running 'pacman -R foo'\n\
+             [2024-10-02 10:00] [ALPM] removed foo (1.0-1)\n",
+        );
+        assert_eq!(log.events.len(), 3);
+        assert_eq!(log.transactions.len(), 2);
+        assert_eq!(log.transactions[0].events, [0, 1]);
+        assert_eq!(log.events[2].transaction, 1);
+        // The state before the removal can be picked again
+        assert_eq!(log.last_transaction_before(at("2024-10-01T12:00:00Z")), Some(0));
+    }
+
+    #[test]
+    fn undoes_later_events() {
+        let log = PacmanLog::parse(LOG);
+        let current: HashMap<String, String> = [("foo", "1.1-1"), ("baz", "3.0-1"), ("glibc", "2.40-1")]
+            .into_iter()
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect();
+
+        assert_eq!(log.last_transaction_before(at("2024-10-01T09:00:00Z")), None);
+        let first = log.last_transaction_before(at("2024-10-01T12:00:00Z"));
+        assert_eq!(first, Some(0));
+
+        let past = log.installed_after(first, &current);
+        assert_eq!(past.len(), 3);
+        assert_eq!(past["foo"], "1.0-1");
+        assert_eq!(past["bar"], "2.0-1");
+        assert_eq!(past["glibc"], "2.40-1");
+
+        let before_log = log.installed_after(None, &current);
+        assert_eq!(before_log.keys().collect::<Vec<_>>(), ["glibc"]);
+
+        let latest = log.installed_after(Some(log.transactions.len() - 1), &current);
+        assert_eq!(latest, current);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{
+    error::AppError,
+    packages::{archive::open_archive, desc::Desc},
+};
+use std::{io::Read, path::Path};
+
+/// Parses the `.PKGINFO` file makepkg puts into every package archive.
+///
+/// The `key = value` format carries the same information as a `desc`
+/// file, so it is read into a [`Desc`]. Keys that appear several times,
+/// like `depend`, collect all their values.
+pub fn parse_pkginfo(content: &str) -> Result<Desc, AppError> {
+    let mut desc = Desc::default();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // Empty values, like `url = `, lost their trailing space to the trim
+        let Some((key, value)) = line.split_once(" =") else {
+            return Err(AppError::ParseError(format!("Invalid .PKGINFO line: {}", line)));
+        };
+        let value = value.trim_start().to_string();
+        match key {
+            "pkgname" => desc.name = value,
+            "pkgbase" => desc.base = Some(value),
+            "pkgver" => desc.version = value,
+            "pkgdesc" => desc.description = value,
+            "url" => desc.url = Some(value),
+            "arch" => desc.arch = Some(value),
+            "builddate" => desc.build_date = Some(parse_number(key, &value)?),
+            "packager" => desc.packager = Some(value),
+            "size" => desc.installed_size = Some(parse_number(key, &value)?),
+            "license" => desc.licenses.push(value),
+            "group" => desc.groups.push(value),
+            "depend" => desc.depends.push(value),
+            "optdepend" => desc.optdepends.push(value),
+            "conflict" => desc.conflicts.push(value),
+            "provides" => desc.provides.push(value),
+            "replaces" => desc.replaces.push(value),
+            _ => {}
+        }
+    }
+    if desc.name.is_empty() || desc.version.is_empty() {
+        return Err(AppError::ParseError(
+            ".PKGINFO lacks pkgname or pkgver".to_string(),
+        ));
+    }
+    Ok(desc)
+}
+
+/// Reads the `.PKGINFO` of a package file. makepkg stores it first, so only
+/// the start of the archive is decompressed.
+pub fn read_package_desc(path: &Path) -> Result<Desc, AppError> {
+    let mut archive = open_archive(path)?;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.as_os_str() == ".PKGINFO" {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            return parse_pkginfo(&content);
+        }
+    }
+    Err(AppError::ParseError(format!(
+        "{} contains no .PKGINFO",
+        path.display()
+    )))
+}
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, AppError> {
+    value
+        .parse()
+        .map_err(|_| AppError::ParseError(format!("Invalid {} in .PKGINFO: {}", key, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fields_and_empty_values() {
+        let desc = parse_pkginfo(
+            "# Generated by makepkg 6.1.0\n\
+             pkgname = foo\n\
+             pkgver = 1:2.0-3\n\
+             pkgdesc = \n\
+             url = \n\
+             builddate = 1726000000\n\
+             size = 4096\n\
+             license = MIT\n\
+             depend = glibc>=2.38\n\
+             depend = sh\n\
+             optdepend = python: for scripts = and hooks\n",
+        )
+        .unwrap();
+        assert_eq!(desc.name, "foo");
+        assert_eq!(desc.version, "1:2.0-3");
+        assert_eq!(desc.description, "");
+        assert_eq!(desc.url.as_deref(), Some(""));
+        assert_eq!(desc.build_date, Some(1726000000));
+        assert_eq!(desc.installed_size, Some(4096));
+        assert_eq!(desc.depends, ["glibc>=2.38", "sh"]);
+        assert_eq!(desc.optdepends, ["python: for scripts = and hooks"]);
+    }
+
+    #[test]
+    fn rejects_invalid_pkginfo() {
+        assert!(parse_pkginfo("pkgname = foo\nnot a field\n").is_err());
+        assert!(parse_pkginfo("pkgname = foo\npkgver = 1.0-1\nsize = big\n").is_err());
+        assert!(parse_pkginfo("pkgname = foo\n").is_err());
+    }
+
+    #[test]
+    fn reads_pkginfo_from_package_files() {
+        let cache = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/example/cache"));
+        let desc = read_package_desc(&cache.join("firefox-130.0.1-1-x86_64.pkg.tar.zst")).unwrap();
+        assert_eq!(desc.name, "firefox");
+        assert_eq!(desc.version, "130.0.1-1");
+        assert_eq!(desc.depends, ["gtk3", "libpulse", "sh"]);
+
+        let desc = read_package_desc(&cache.join("python-oldlib-0.3-1-any.pkg.tar.xz")).unwrap();
+        assert_eq!(desc.arch.as_deref(), Some("any"));
+    }
+}
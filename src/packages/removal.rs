@@ -0,0 +1,169 @@
+use crate::packages::{deps::DependencyGraph, models::Package};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// What `pacman -Rns` would do to the installed system.
+#[derive(Debug, Clone, Default)]
+pub struct RemovalImpact {
+    /// The packages asked to be removed
+    pub targets: BTreeSet<String>,
+    /// Dependencies removed along with the targets because nothing else
+    /// needs them anymore
+    pub dependencies: BTreeSet<String>,
+    /// Packages staying installed that require a removed package, mapped to
+    /// the removed packages they require. pacman refuses the removal while
+    /// this is not empty.
+    pub broken: BTreeMap<String, Vec<String>>,
+    /// Packages staying installed that lose an optional dependency
+    pub optional_lost: BTreeMap<String, Vec<String>>,
+    /// Installed size of everything removed, in MiB
+    pub freed_mib: f64,
+}
+
+impl RemovalImpact {
+    /// Computes the removal of `targets` with recursive removal of unneeded
+    /// dependencies, mirroring pacman's `-s` rules: a dependency goes too if
+    /// it was not explicitly installed and everything requiring it is
+    /// removed as well.
+    pub fn compute<'a>(
+        targets: impl IntoIterator<Item = &'a str>,
+        packages: &[Package],
+        graph: &DependencyGraph,
+    ) -> Self {
+        let by_name: HashMap<&str, &Package> =
+            packages.iter().map(|p| (p.name.as_str(), p)).collect();
+        let targets: BTreeSet<String> = targets
+            .into_iter()
+            .filter(|name| by_name.contains_key(name))
+            .map(str::to_string)
+            .collect();
+
+        let mut removed = targets.clone();
+        let mut dependencies = BTreeSet::new();
+        loop {
+            let unneeded: Vec<String> = removed
+                .iter()
+                .flat_map(|name| graph.depends_on(name))
+                .filter(|dep| !removed.contains(*dep))
+                .filter(|dep| by_name.get(dep.as_str()).is_some_and(|p| !p.is_explicit))
+                .filter(|dep| graph.required_by(dep).iter().all(|r| removed.contains(r)))
+                .cloned()
+                .collect();
+            if unneeded.is_empty() {
+                break;
+            }
+            for dep in unneeded {
+                removed.insert(dep.clone());
+                dependencies.insert(dep);
+            }
+        }
+
+        let mut broken: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut optional_lost: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for name in &removed {
+            for dependent in graph.required_by(name).iter().filter(|r| !removed.contains(*r)) {
+                broken.entry(dependent.clone()).or_default().push(name.clone());
+            }
+            for dependent in graph.optional_for(name).iter().filter(|r| !removed.contains(*r)) {
+                optional_lost.entry(dependent.clone()).or_default().push(name.clone());
+            }
+        }
+
+        let freed_mib = removed
+            .iter()
+            .filter_map(|name| by_name.get(name.as_str()))
+            .map(|p| p.size)
+            .sum();
+
+        Self {
+            targets,
+            dependencies,
+            broken,
+            optional_lost,
+            freed_mib,
+        }
+    }
+
+    /// Computes the removal of every installed package carrying `tag`.
+    pub fn for_tag(tag: &str, packages: &[Package], graph: &DependencyGraph) -> Self {
+        let targets = packages
+            .iter()
+            .filter(|p| p.tags.iter().any(|t| t == tag))
+            .map(|p| p.name.as_str());
+        Self::compute(targets, packages, graph)
+    }
+
+    pub fn removed_count(&self) -> usize {
+        self.targets.len() + self.dependencies.len()
+    }
+
+    /// Whether pacman would carry out the removal without `-c` or `-dd`.
+    pub fn is_safe(&self) -> bool {
+        self.broken.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compute(targets: &[&str], packages: &[Package]) -> RemovalImpact {
+        let graph = DependencyGraph::build(packages);
+        RemovalImpact::compute(targets.iter().copied(), packages, &graph)
+    }
+
+    #[test]
+    fn reports_packages_that_would_break() {
+        let packages = [
+            Package::installed("app", true, &["lib"]),
+            Package::installed("tool", true, &["lib>=1.0"]),
+            Package::installed("lib", false, &[]),
+        ];
+        let impact = compute(&["lib"], &packages);
+        assert!(!impact.is_safe());
+        assert_eq!(impact.broken.keys().collect::<Vec<_>>(), ["app", "tool"]);
+        assert_eq!(impact.broken["app"], ["lib"]);
+
+        // Removing every dependent along with it is fine
+        assert!(compute(&["lib", "app", "tool"], &packages).is_safe());
+    }
+
+    #[test]
+    fn removes_dependencies_nothing_else_needs() {
+        let mut viewer = Package::installed("viewer", true, &[]);
+        viewer.optional_deps = vec!["app: for previews".to_string()];
+        let mut packages = vec![
+            Package::installed("app", true, &["libfoo", "libbar", "editor"]),
+            Package::installed("libfoo", false, &["libbaz"]),
+            Package::installed("libbar", false, &[]),
+            Package::installed("libbaz", false, &[]),
+            Package::installed("other", true, &["libbar"]),
+            // Explicitly installed dependencies stay
+            Package::installed("editor", true, &[]),
+            viewer,
+        ];
+        for (pkg, size) in packages.iter_mut().zip([10.0, 2.0, 4.0, 1.0, 8.0, 16.0, 32.0]) {
+            pkg.size = size;
+        }
+
+        let impact = compute(&["app", "not-installed"], &packages);
+        assert_eq!(impact.targets.iter().collect::<Vec<_>>(), ["app"]);
+        assert_eq!(impact.dependencies.iter().collect::<Vec<_>>(), ["libbaz", "libfoo"]);
+        assert_eq!(impact.removed_count(), 3);
+        assert_eq!(impact.freed_mib, 13.0);
+        assert!(impact.is_safe());
+        assert_eq!(impact.optional_lost["viewer"], ["app"]);
+    }
+
+    #[test]
+    fn follows_dependencies_through_provides() {
+        let mut bash = Package::installed("bash", false, &[]);
+        bash.provides = vec!["sh=5.2".to_string()];
+        let packages = [Package::installed("script", true, &["sh"]), bash];
+
+        let impact = compute(&["script"], &packages);
+        assert_eq!(impact.dependencies.iter().collect::<Vec<_>>(), ["bash"]);
+
+        let impact = compute(&["bash"], &packages);
+        assert_eq!(impact.broken["script"], ["bash"]);
+    }
+}
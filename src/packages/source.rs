@@ -0,0 +1,70 @@
+use crate::{
+    error::AppError,
+    packages::{
+        aur::AurLookup, desc::Desc, local_db::{MtreeEntry, PackageFiles}, pacman_log::PacmanLog,
+        sync_db::SyncPackage,
+    },
+};
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Where the application reads its package data from.
+///
+/// The live implementation is [`PacmanSource`](crate::packages::pacman::PacmanSource);
+/// [`FixtureSource`](crate::packages::fixture::FixtureSource) serves captured data
+/// so the app can run on machines without pacman.
+pub trait PackageSource: Send + Sync {
+    /// `desc` entries of all installed packages. Entries that cannot be read
+    /// are skipped and reported in `warnings`.
+    fn local_packages(&self, warnings: &mut Vec<String>) -> Result<Vec<Desc>, AppError>;
+
+    /// Packages of all sync repositories, in repository priority order.
+    /// Entries that cannot be read are skipped and reported in `warnings`.
+    fn sync_packages(&self, warnings: &mut Vec<String>) -> Result<Vec<SyncPackage>, AppError>;
+
+    /// The installation root that package file paths are relative to.
+    fn root(&self) -> PathBuf;
+
+    /// Directories holding downloaded package files.
+    fn cache_dirs(&self) -> Vec<PathBuf>;
+
+    /// Files owned by an installed package.
+    fn package_files(&self, name: &str, version: &str) -> Result<PackageFiles, AppError>;
+
+    /// `mtree` entries of an installed package, recording the properties of
+    /// every path as packaged.
+    fn package_mtree(&self, name: &str, version: &str) -> Result<Vec<MtreeEntry>, AppError>;
+
+    /// The package history recorded in the pacman log.
+    fn pacman_log(&self) -> Result<PacmanLog, AppError>;
+
+    /// Command setting the install reason of installed packages, like
+    /// `pacman -D`. `None` if the source is read-only, in which case the
+    /// change is only applied in memory.
+    fn install_reason_command(&self, package_names: &[String], explicit: bool) -> Option<Vec<String>>;
+
+    /// Command deleting files that belong to root, such as cached packages.
+    /// `None` if the source is read-only, in which case the files are only
+    /// forgotten in memory.
+    fn remove_files_command(&self, paths: &[&Path]) -> Option<Vec<String>>;
+
+    /// Command moving a file that belongs to root over another one.
+    /// `None` if the source is read-only.
+    fn move_file_command(&self, from: &Path, to: &Path) -> Option<Vec<String>>;
+
+    /// Command opening two versions of a config file in the configured merge
+    /// tool. `None` if the source is read-only.
+    fn merge_files_command(&self, old: &Path, new: &Path) -> Option<Vec<String>>;
+
+    /// Command installing package files, like `pacman -U`. `None` if the
+    /// source is read-only.
+    fn install_files_command(&self, paths: &[&Path]) -> Option<Vec<String>>;
+
+    /// AUR metadata for those of the given packages that exist on the AUR.
+    fn aur_packages(&self, package_names: Vec<String>) -> BoxFuture<'_, Result<AurLookup, AppError>>;
+}
@@ -0,0 +1,162 @@
+use crate::{
+    config::PacmanConfig,
+    error::AppError,
+    packages::{
+        archive::open_archive,
+        desc::{parse_desc, Desc},
+    },
+};
+use std::{fs, io::Read, path::PathBuf};
+
+/// Reader for pacman's sync databases (`<dbpath>/sync/<repo>.db`).
+pub struct SyncDb {
+    db_path: PathBuf,
+}
+
+/// A package entry read from one of the sync databases.
+#[derive(Debug, Clone)]
+pub struct SyncPackage {
+    pub repository: String,
+    pub desc: Desc,
+}
+
+impl SyncDb {
+    pub fn new(db_path: impl Into<PathBuf>) -> Self {
+        Self {
+            db_path: db_path.into(),
+        }
+    }
+
+    pub fn from_config(config: &PacmanConfig) -> Self {
+        Self::new(&config.db_path)
+    }
+
+    fn sync_dir(&self) -> PathBuf {
+        self.db_path.join("sync")
+    }
+
+    /// Names of all repositories that have a database on disk, sorted by name.
+    pub fn repo_names(&self) -> Result<Vec<String>, AppError> {
+        let mut names: Vec<String> = fs::read_dir(self.sync_dir())?
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                match path.extension() {
+                    Some(ext) if ext == "db" => {
+                        path.file_stem().map(|s| s.to_string_lossy().into_owned())
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Reads every package of every repository database.
+    pub fn read_packages(&self, warnings: &mut Vec<String>) -> Result<Vec<SyncPackage>, AppError> {
+        self.read_repos(&self.repo_names()?, warnings)
+    }
+
+    /// Reads the given repositories in order.
+    ///
+    /// Repositories without a database on disk (e.g. never synced) are skipped.
+    /// So are unreadable databases, like truncated downloads, with a warning,
+    /// instead of losing the other repositories too.
+    pub fn read_repos(
+        &self,
+        repos: &[String],
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<SyncPackage>, AppError> {
+        let mut packages = Vec::new();
+        for repo in repos {
+            let path = self.sync_dir().join(format!("{}.db", repo));
+            if !path.exists() {
+                continue;
+            }
+            match self.read_repo(repo, warnings) {
+                Ok(repo_packages) => packages.extend(repo_packages),
+                Err(e) => warnings.push(format!(
+                    "Skipped repository {} ({}): {}",
+                    repo,
+                    path.display(),
+                    e
+                )),
+            }
+        }
+        Ok(packages)
+    }
+
+    /// Reads the packages of a single repository database.
+    ///
+    /// Entries that cannot be parsed are skipped with a warning, like in the
+    /// local database.
+    pub fn read_repo(&self, repo: &str, warnings: &mut Vec<String>) -> Result<Vec<SyncPackage>, AppError> {
+        let path = self.sync_dir().join(format!("{}.db", repo));
+        // repo-add writes gzip databases by default, but other compressions
+        // and uncompressed tarballs are accepted as well
+        let mut archive = open_archive(&path)?;
+        let mut packages = Vec::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.path()?.ends_with("desc") {
+                continue;
+            }
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            match parse_desc(&content) {
+                Ok(desc) => packages.push(SyncPackage {
+                    repository: repo.to_string(),
+                    desc,
+                }),
+                Err(e) => warnings.push(format!(
+                    "Skipped {} entry in {}: {}",
+                    repo,
+                    path.display(),
+                    e
+                )),
+            }
+        }
+        Ok(packages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_repository_and_skips_broken_entries() {
+        let sync_db = SyncDb::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/example/db"));
+        assert_eq!(sync_db.repo_names().unwrap(), ["core"]);
+
+        let mut warnings = Vec::new();
+        let packages = sync_db.read_packages(&mut warnings).unwrap();
+        let names: Vec<&str> = packages.iter().map(|p| p.desc.name.as_str()).collect();
+        assert_eq!(names, ["bash", "glibc"]);
+        assert!(packages.iter().all(|p| p.repository == "core"));
+        assert_eq!(packages[0].desc.download_size, Some(1887436));
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn skips_unreadable_repositories() {
+        let dir = tempfile::tempdir().unwrap();
+        let sync_dir = dir.path().join("sync");
+        fs::create_dir(&sync_dir).unwrap();
+        let core = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/example/db/sync/core.db");
+        fs::copy(core, sync_dir.join("core.db")).unwrap();
+        // A download cut off after a few bytes
+        let bytes = fs::read(core).unwrap();
+        fs::write(sync_dir.join("extra.db"), &bytes[..20]).unwrap();
+
+        let sync_db = SyncDb::new(dir.path());
+        let mut warnings = Vec::new();
+        let repos = ["extra".to_string(), "core".to_string(), "multilib".to_string()];
+        let packages = sync_db.read_repos(&repos, &mut warnings).unwrap();
+        let names: Vec<&str> = packages.iter().map(|p| p.desc.name.as_str()).collect();
+        assert_eq!(names, ["bash", "glibc"]);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("Skipped repository extra"), "{}", warnings[0]);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::packages::{
+    models::Package,
+    pacman_log::PacmanLog,
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// How a package changed between the past moment and today.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Unchanged,
+    /// Not installed back then
+    InstalledSince,
+    /// Installed back then, but not anymore
+    RemovedSince,
+    /// Installed in a different version today
+    UpdatedSince { now: String },
+}
+
+/// The installed package set at a past moment, reconstructed from pacman.log.
+pub struct TimeTravel {
+    pub moment: DateTime<Utc>,
+    /// Packages installed back then, in their old versions, followed by
+    /// packages that were installed since
+    pub packages: Vec<Package>,
+    pub changes: HashMap<String, Change>,
+}
+
+impl TimeTravel {
+    /// Reconstructs the state right after `transaction` of the log.
+    ///
+    /// `installed` and `available` provide the package details, which the
+    /// log does not record; only names and versions come from the log.
+    pub fn build(
+        log: &PacmanLog,
+        transaction: Option<usize>,
+        moment: DateTime<Utc>,
+        installed: &[Package],
+        available: &[Package],
+    ) -> Self {
+        let current: HashMap<String, String> = installed
+            .iter()
+            .map(|p| (p.name.clone(), p.version.clone()))
+            .collect();
+        let past = log.installed_after(transaction, &current);
+
+        let installed_by_name: HashMap<&str, &Package> =
+            installed.iter().map(|p| (p.name.as_str(), p)).collect();
+        // The first repository wins, as in the available package list
+        let mut available_by_name: HashMap<&str, &Package> = HashMap::new();
+        for pkg in available {
+            available_by_name.entry(pkg.name.as_str()).or_insert(pkg);
+        }
+
+        let mut packages = Vec::new();
+        let mut changes = HashMap::new();
+        for (name, version) in &past {
+            let (mut package, change) = match installed_by_name.get(name.as_str()) {
+                Some(&now) if now.version == *version => (now.clone(), Change::Unchanged),
+                Some(&now) => (
+                    now.clone(),
+                    Change::UpdatedSince { now: now.version.clone() },
+                ),
+                None => {
+                    let package = match available_by_name.get(name.as_str()) {
+                        Some(&pkg) => pkg.clone(),
+                        None => Package::unknown(name),
+                    };
+                    (package, Change::RemovedSince)
+                }
+            };
+            package.version = version.clone();
+            packages.push(package);
+            changes.insert(name.clone(), change);
+        }
+        for pkg in installed.iter().filter(|p| !past.contains_key(&p.name)) {
+            packages.push(pkg.clone());
+            changes.insert(pkg.name.clone(), Change::InstalledSince);
+        }
+
+        Self {
+            moment,
+            packages,
+            changes,
+        }
+    }
+
+    /// Number of packages that were installed at the moment
+    pub fn installed_count(&self) -> usize {
+        self.changes.values().filter(|c| **c != Change::InstalledSince).count()
+    }
+
+    /// Number of packages that differ from today
+    pub fn changed_count(&self) -> usize {
+        self.changes.values().filter(|c| **c != Change::Unchanged).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::pacman_log::tests::LOG;
+
+    fn package(name: &str, version: &str) -> Package {
+        let mut pkg = Package::unknown(name);
+        pkg.version = version.to_string();
+        pkg
+    }
+
+    #[test]
+    fn reconstructs_package_set_at_a_moment() {
+        let log = PacmanLog::parse(LOG);
+        let moment = DateTime::parse_from_rfc3339("2024-10-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let installed = [
+            package("foo", "1.1-1"),
+            package("baz", "3.0-1"),
+            package("glibc", "2.40-1"),
+        ];
+        let mut bar = package("bar", "2.1-1");
+        bar.description = "From the sync database".to_string();
+
+        let travel = TimeTravel::build(
+            &log,
+            log.last_transaction_before(moment),
+            moment,
+            &installed,
+            &[bar],
+        );
+
+        let version = |name: &str| {
+            travel
+                .packages
+                .iter()
+                .find(|p| p.name == name)
+                .map(|p| p.version.as_str())
+        };
+        assert_eq!(version("foo"), Some("1.0-1"));
+        assert_eq!(version("bar"), Some("2.0-1"));
+        assert_eq!(version("glibc"), Some("2.40-1"));
+        assert_eq!(version("baz"), Some("3.0-1"));
+
+        assert_eq!(travel.changes["foo"], Change::UpdatedSince { now: "1.1-1".to_string() });
+        assert_eq!(travel.changes["bar"], Change::RemovedSince);
+        assert_eq!(travel.changes["baz"], Change::InstalledSince);
+        assert_eq!(travel.changes["glibc"], Change::Unchanged);
+        assert_eq!(travel.installed_count(), 3);
+        assert_eq!(travel.changed_count(), 3);
+
+        // Removed packages keep the details of their sync entry
+        let bar = travel.packages.iter().find(|p| p.name == "bar").unwrap();
+        assert_eq!(bar.description, "From the sync database");
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{config::UnownedConfig, packages::files::FileOwners};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// A file or directory no installed package owns.
+#[derive(Debug, Clone)]
+pub struct UnownedPath {
+    /// Path relative to the installation root; directories end with `/`
+    pub path: String,
+    /// Size in bytes, for a directory the total of everything below it
+    pub size: u64,
+}
+
+impl UnownedPath {
+    pub fn is_dir(&self) -> bool {
+        self.path.ends_with('/')
+    }
+
+    /// The last path component, with a trailing `/` for directories
+    pub fn name(&self) -> &str {
+        let trimmed = self.path.trim_end_matches('/');
+        let start = trimmed.rfind('/').map_or(0, |i| i + 1);
+        &self.path[start..]
+    }
+}
+
+/// The unowned paths directly inside one directory.
+#[derive(Debug, Clone)]
+pub struct UnownedDir {
+    /// Path relative to the installation root, ending with `/` unless it is
+    /// the root itself
+    pub path: String,
+    /// Sorted by path
+    pub entries: Vec<UnownedPath>,
+    pub size: u64,
+}
+
+/// Files and directories under the scanned roots that no installed package
+/// owns, like leftovers of `make install` or of removed packages.
+///
+/// An unowned directory is reported as a whole instead of every path below it.
+#[derive(Debug, Clone, Default)]
+pub struct UnownedFiles {
+    /// Largest first
+    dirs: Vec<UnownedDir>,
+}
+
+impl UnownedFiles {
+    /// Walks the configured roots below the installation root without
+    /// following symlinks. Unreadable directories are skipped.
+    pub fn scan(root: &Path, owners: &FileOwners, config: &UnownedConfig) -> Self {
+        let exclude: Vec<String> = config.exclude.iter().map(|p| relative(p)).collect();
+        let mut walker = Walker {
+            root,
+            owners,
+            exclude: &exclude,
+            found: BTreeMap::new(),
+        };
+        for scan_root in &config.roots {
+            let dir = relative(scan_root);
+            if !walker.is_excluded(&dir) {
+                walker.walk(&dir);
+            }
+        }
+
+        let mut dirs: Vec<UnownedDir> = walker
+            .found
+            .into_iter()
+            .map(|(path, entries)| UnownedDir {
+                size: entries.iter().map(|e| e.size).sum(),
+                path,
+                entries,
+            })
+            .collect();
+        dirs.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        Self { dirs }
+    }
+
+    pub fn dirs(&self) -> &[UnownedDir] {
+        &self.dirs
+    }
+
+    /// Number of reported paths
+    pub fn path_count(&self) -> usize {
+        self.dirs.iter().map(|d| d.entries.len()).sum()
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.dirs.iter().map(|d| d.size).sum()
+    }
+}
+
+struct Walker<'a> {
+    root: &'a Path,
+    owners: &'a FileOwners,
+    /// Relative paths without a trailing `/`
+    exclude: &'a [String],
+    /// Directory -> its unowned entries
+    found: BTreeMap<String, Vec<UnownedPath>>,
+}
+
+impl Walker<'_> {
+    fn is_excluded(&self, path: &str) -> bool {
+        self.exclude.iter().any(|ex| {
+            path.strip_prefix(ex.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    // `dir` is relative to the root and has no trailing `/`
+    fn walk(&mut self, dir: &str) {
+        let Ok(entries) = fs::read_dir(self.root.join(dir)) else {
+            return;
+        };
+        let mut entries: Vec<_> = entries.flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = join(dir, &entry.file_name().to_string_lossy());
+            if self.is_excluded(&path) {
+                continue;
+            }
+            if file_type.is_dir() {
+                let dir_path = format!("{}/", path);
+                if self.owners.contains(&dir_path) {
+                    self.walk(&path);
+                } else {
+                    let size = self.dir_size(&path);
+                    self.report(dir, dir_path, size);
+                }
+            } else if !self.owners.contains(&path) {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or_default();
+                self.report(dir, path, size);
+            }
+        }
+    }
+
+    fn report(&mut self, dir: &str, path: String, size: u64) {
+        self.found
+            .entry(if dir.is_empty() { String::new() } else { format!("{}/", dir) })
+            .or_default()
+            .push(UnownedPath { path, size });
+    }
+
+    // Total size of the files and symlinks below a directory
+    fn dir_size(&self, dir: &str) -> u64 {
+        let Ok(entries) = fs::read_dir(self.root.join(dir)) else {
+            return 0;
+        };
+        entries
+            .flatten()
+            .map(|entry| {
+                let path = join(dir, &entry.file_name().to_string_lossy());
+                match entry.file_type() {
+                    _ if self.is_excluded(&path) => 0,
+                    Ok(file_type) if file_type.is_dir() => self.dir_size(&path),
+                    Ok(_) => entry.metadata().map(|m| m.len()).unwrap_or_default(),
+                    Err(_) => 0,
+                }
+            })
+            .sum()
+    }
+}
+
+// Configured paths are absolute system paths, resolved below the installation root
+fn relative(path: &Path) -> String {
+    path.to_string_lossy().trim_matches('/').to_string()
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::{fixture::FixtureSource, models::Package};
+    use std::{os::unix::fs::symlink, path::PathBuf};
+
+    fn write_file(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    // A fixture directory whose only package, `app`, owns a few paths of
+    // a root that also holds leftovers
+    fn app_fixture() -> (tempfile::TempDir, FileOwners) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("files.json"),
+            r#"{"app": {"files": ["etc/", "etc/app.conf", "usr/", "usr/bin/", "usr/bin/app"]}}"#,
+        )
+        .unwrap();
+        let root = dir.path().join("root");
+        write_file(&root, "etc/app.conf", "config");
+        write_file(&root, "etc/ld.so.cache", "cache");
+        write_file(&root, "usr/bin/app", "binary");
+        write_file(&root, "usr/bin/stray", "leftover");
+        symlink("app", root.join("usr/bin/applink")).unwrap();
+        write_file(&root, "usr/local/bin/mytool", "0123456789");
+        write_file(&root, "usr/local/share/doc", "12345");
+        write_file(&root, "usr/local/cache/blob", "ignored");
+
+        let packages = [Package::installed("app", true, &[])];
+        let owners = FileOwners::build(&FixtureSource::new(dir.path()), &packages);
+        (dir, owners)
+    }
+
+    fn config() -> UnownedConfig {
+        UnownedConfig {
+            roots: ["/usr", "/etc", "/opt"].map(PathBuf::from).to_vec(),
+            exclude: ["/etc/ld.so.cache", "/usr/local/cache"].map(PathBuf::from).to_vec(),
+        }
+    }
+
+    fn listing(files: &UnownedFiles) -> Vec<(String, Vec<(String, u64)>)> {
+        files
+            .dirs()
+            .iter()
+            .map(|dir| {
+                let entries = dir.entries.iter().map(|e| (e.path.clone(), e.size)).collect();
+                (dir.path.clone(), entries)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_unowned_paths_largest_directory_first() {
+        let (dir, owners) = app_fixture();
+        let files = UnownedFiles::scan(&dir.path().join("root"), &owners, &config());
+
+        // usr/local/ is reported as a whole, without its excluded cache, and
+        // the missing /opt root is skipped
+        assert_eq!(
+            listing(&files),
+            [
+                ("usr/".to_string(), vec![("usr/local/".to_string(), 15)]),
+                (
+                    "usr/bin/".to_string(),
+                    vec![("usr/bin/applink".to_string(), 3), ("usr/bin/stray".to_string(), 8)]
+                ),
+            ]
+        );
+        assert_eq!(files.path_count(), 3);
+        assert_eq!(files.total_size(), 26);
+    }
+
+    #[test]
+    fn excluded_roots_are_not_walked() {
+        let (dir, owners) = app_fixture();
+        let config = UnownedConfig {
+            exclude: vec![PathBuf::from("/usr")],
+            ..config()
+        };
+        let files = UnownedFiles::scan(&dir.path().join("root"), &owners, &config);
+
+        // ld.so.cache is no longer excluded either
+        assert_eq!(listing(&files), [("etc/".to_string(), vec![("etc/ld.so.cache".to_string(), 5)])]);
+    }
+
+    #[test]
+    fn names_keep_the_directory_slash() {
+        let dir = UnownedPath { path: "usr/local/".to_string(), size: 0 };
+        let file = UnownedPath { path: "usr/bin/stray".to_string(), size: 0 };
+        let top = UnownedPath { path: "stray".to_string(), size: 0 };
+
+        assert_eq!((dir.name(), dir.is_dir()), ("local/", true));
+        assert_eq!((file.name(), file.is_dir()), ("stray", false));
+        assert_eq!(top.name(), "stray");
+    }
+}
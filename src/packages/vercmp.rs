@@ -0,0 +1,199 @@
+//! Port of pacman's `alpm_pkg_vercmp`, which orders versions of the form
+//! `[epoch:]pkgver[-pkgrel]` the same way pacman decides upgrades.
+
+use std::cmp::Ordering;
+
+/// Compares two package versions like `vercmp(8)`.
+pub fn vercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (epoch_a, version_a, release_a) = parse_evr(a);
+    let (epoch_b, version_b, release_b) = parse_evr(b);
+
+    rpmvercmp(epoch_a, epoch_b)
+        .then_with(|| rpmvercmp(version_a, version_b))
+        .then_with(|| match (release_a, release_b) {
+            // A missing pkgrel matches any pkgrel
+            (Some(release_a), Some(release_b)) => rpmvercmp(release_a, release_b),
+            _ => Ordering::Equal,
+        })
+}
+
+/// Splits `[epoch:]version[-release]`; the epoch defaults to "0".
+fn parse_evr(evr: &str) -> (&str, &str, Option<&str>) {
+    let digits = evr.bytes().take_while(u8::is_ascii_digit).count();
+    let (epoch, rest) = match evr.as_bytes().get(digits) {
+        Some(b':') if digits > 0 => (&evr[..digits], &evr[digits + 1..]),
+        Some(b':') => ("0", &evr[1..]),
+        _ => ("0", evr),
+    };
+    match rest.rsplit_once('-') {
+        Some((version, release)) => (epoch, version, Some(release)),
+        None => (epoch, rest, None),
+    }
+}
+
+/// Compares version segments the way rpm (and pacman) does: alternating runs
+/// of digits and letters, split at any other character.
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut one, mut two) = (0, 0);
+
+    while one < a.len() && two < b.len() {
+        let separator_start = (one, two);
+        while one < a.len() && !a[one].is_ascii_alphanumeric() {
+            one += 1;
+        }
+        while two < b.len() && !b[two].is_ascii_alphanumeric() {
+            two += 1;
+        }
+        if one >= a.len() || two >= b.len() {
+            break;
+        }
+        // Segments with a different amount of separators: the longer one is newer
+        let separators = (one - separator_start.0, two - separator_start.1);
+        if separators.0 != separators.1 {
+            return separators.0.cmp(&separators.1);
+        }
+
+        let is_num = a[one].is_ascii_digit();
+        let segment_end = |s: &[u8], start: usize| {
+            start
+                + s[start..]
+                    .iter()
+                    .take_while(|c| if is_num { c.is_ascii_digit() } else { c.is_ascii_alphabetic() })
+                    .count()
+        };
+        let (end_one, end_two) = (segment_end(a, one), segment_end(b, two));
+
+        // A numeric segment is always newer than an alpha one
+        if two == end_two {
+            return if is_num { Ordering::Greater } else { Ordering::Less };
+        }
+
+        let (mut seg_one, mut seg_two) = (&a[one..end_one], &b[two..end_two]);
+        if is_num {
+            seg_one = trim_leading_zeros(seg_one);
+            seg_two = trim_leading_zeros(seg_two);
+            let by_length = seg_one.len().cmp(&seg_two.len());
+            if by_length != Ordering::Equal {
+                return by_length;
+            }
+        }
+        let by_content = seg_one.cmp(seg_two);
+        if by_content != Ordering::Equal {
+            return by_content;
+        }
+
+        one = end_one;
+        two = end_two;
+    }
+
+    let (rest_one, rest_two) = (a.get(one), b.get(two));
+    match (rest_one, rest_two) {
+        (None, None) => Ordering::Equal,
+        // A remaining alpha segment never beats an empty one: 1.0 > 1.0a, but 1.0.1 > 1.0
+        (None, Some(c)) if !c.is_ascii_alphabetic() => Ordering::Less,
+        (Some(c), _) if c.is_ascii_alphabetic() => Ordering::Less,
+        _ => Ordering::Greater,
+    }
+}
+
+fn trim_leading_zeros(segment: &[u8]) -> &[u8] {
+    let zeros = segment.iter().take_while(|&&c| c == b'0').count();
+    &segment[zeros..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cases from pacman's test/util/vercmptest.sh; each is also checked reversed
+    const CASES: &[(&str, &str, i8)] = &[
+        // all similar length, no pkgrel
+        ("1.5.0", "1.5.0", 0),
+        ("1.5.1", "1.5.0", 1),
+        // mixed length
+        ("1.5.1", "1.5", 1),
+        // with pkgrel, simple
+        ("1.5.0-1", "1.5.0-1", 0),
+        ("1.5.0-1", "1.5.0-2", -1),
+        ("1.5.0-1", "1.5.1-1", -1),
+        ("1.5.0-2", "1.5.1-1", -1),
+        // with pkgrel, mixed lengths
+        ("1.5-1", "1.5.1-1", -1),
+        ("1.5-2", "1.5.1-1", -1),
+        ("1.5-2", "1.5.1-2", -1),
+        // mixed pkgrel inclusion
+        ("1.5", "1.5-1", 0),
+        ("1.5-1", "1.5", 0),
+        ("1.1-1", "1.1", 0),
+        ("1.0-1", "1.1", -1),
+        ("1.1-1", "1.0", 1),
+        // alphanumeric versions
+        ("1.5b-1", "1.5-1", -1),
+        ("1.5b", "1.5", -1),
+        ("1.5b-1", "1.5", -1),
+        ("1.5b", "1.5.1", -1),
+        // from the manpage
+        ("1.0a", "1.0alpha", -1),
+        ("1.0alpha", "1.0b", -1),
+        ("1.0b", "1.0beta", -1),
+        ("1.0beta", "1.0rc", -1),
+        ("1.0rc", "1.0", -1),
+        // alpha-dotted versions
+        ("1.5.a", "1.5", 1),
+        ("1.5.b", "1.5.a", 1),
+        ("1.5.1", "1.5.b", 1),
+        // alpha dots and dashes
+        ("1.5.b-1", "1.5.b", 0),
+        ("1.5-1", "1.5.b", -1),
+        // same/similar content, differing separators
+        ("2.0", "2_0", 0),
+        ("2.0_a", "2_0.a", 0),
+        ("2.0a", "2.0.a", -1),
+        ("2___a", "2_a", 1),
+        // epoch included version comparisons
+        ("0:1.0", "0:1.0", 0),
+        ("0:1.0", "0:1.1", -1),
+        ("1:1.0", "0:1.0", 1),
+        ("1:1.0", "0:1.1", 1),
+        ("1:1.0", "2:1.1", -1),
+        // epoch + sometimes present pkgrel
+        ("1:1.0", "0:1.0-1", 1),
+        ("1:1.0-1", "0:1.1-1", 1),
+        // epoch included on one version
+        ("0:1.0", "1.0", 0),
+        ("0:1.1", "1.0", 1),
+        ("0:1.1", "1.1", 0),
+        ("1:1.0", "1.0", 1),
+        ("1:1.1", "1.1", 1),
+        ("1:1.1", "1.11", 1),
+        // git versions
+        ("1.0.r1.g0123456", "1.0.r2.g6543210", -1),
+        ("1.0.r10.g0123456", "1.0.r9.g6543210", 1),
+        // leading zeros
+        ("1.01", "1.1", 0),
+        ("1.001", "1.1", 0),
+        ("1.010", "1.10", 0),
+        ("1.010", "1.9", 1),
+        ("01:1.0", "1:1.0", 0),
+        // `~` is a plain separator like `.`, unlike in dpkg
+        ("1.0~rc1", "1.0.rc1", 0),
+        ("1.0~rc1", "1.0", 1),
+        ("1.0~1", "1.0.1", 0),
+    ];
+
+    #[test]
+    fn matches_pacman_vercmp() {
+        for &(a, b, expected) in CASES {
+            let expected = expected.cmp(&0);
+            assert_eq!(vercmp(a, b), expected, "vercmp({}, {})", a, b);
+            assert_eq!(vercmp(b, a), expected.reverse(), "vercmp({}, {})", b, a);
+        }
+    }
+}
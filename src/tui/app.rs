@@ -4,22 +4,49 @@ use fuzzy_matcher::FuzzyMatcher;
 use ratatui::prelude::*;
 use ratatui::widgets::ListState;
 use ratatui::Terminal;
+use chrono::{DateTime, Local, Utc};
+use std::collections::BTreeSet;
 use std::io::Stdout;
-use tokio::sync::mpsc;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, error::TryRecvError};
+use tokio::sync::oneshot;
 
-use crate::packages::models::ShowMode;
+use crate::packages::cache::{CachedFile, PackageCache};
+use crate::packages::config_drift::{ConfigDrift, DriftAction, DriftFile, DriftKind};
+use crate::packages::files::{file_statuses, FileOwners, FileStatus};
+use crate::packages::integrity;
+use crate::packages::modified_configs::ModifiedConfigs;
+use crate::packages::models::{Package, ShowMode};
+use crate::packages::orphans::Orphans;
+use crate::packages::pacman::bytes_to_mib;
+use crate::packages::pkginfo::read_package_desc;
+use crate::packages::removal::RemovalImpact;
+use crate::packages::source::PackageSource;
+use crate::packages::time_travel::TimeTravel;
+use crate::packages::unowned::UnownedFiles;
 use crate::tui::app_states::{
-    app_state::{AppState, InputMode, LoadedData},
+    app_state::{AppState, InfoTab, InputMode, IntegrityCheck, LoadedData, SystemChange},
     filter_modal_state::FilterModalState,
     message_log::OutputLog,
     normal_state::NormalState,
     action_modal_state::ActionModalState,
+    dependency_tree_state::DependencyTreeState,
+    transactions_state::TransactionsState,
+    time_travel_state::TimeTravelState,
+    owner_search_state::OwnerSearchState,
+    removal_preview_state::RemovalPreviewState,
+    cache_cleanup_state::CacheCleanupState,
+    config_drift_state::ConfigDriftState,
+    downgrade_state::{CachedVersion, DowngradeState},
+    unowned_state::UnownedState,
     search_state::SearchState,
     show_mode_state::ShowModeState,
     sort_state::SortState,
     tag_modal_state::TagModalState,
 };
 use crate::tui::event::handle_events;
+use crate::tui::terminal::{init_terminal, restore_terminal};
 use crate::tui::ui;
 
 pub struct App {
@@ -32,6 +59,18 @@ pub struct App {
     pub config: config::Config,
     pub output_log_area: Rect, 
     pub package_list_area: Rect,
+    pub info_scroll: u16,
+    pub info_tab: InfoTab,
+    /// Whether the package information starts with why the package is installed
+    pub show_why_installed: bool,
+    /// File list of the package shown in the files tab
+    pub package_files: Option<(String, Result<Vec<FileStatus>, String>)>,
+    /// Packages marked for actions that work on several packages at once
+    pub marked_packages: BTreeSet<String>,
+    /// Change to run with the TUI suspended before the next frame
+    pub system_change: Option<SystemChange>,
+
+    pub dependency_filter: Option<backend::DependencyFilter>,
 
     pub search_input: String,
     pub search_cursor_position: usize,
@@ -42,13 +81,30 @@ pub struct App {
     pub normal_state: NormalState,
     pub search_state: SearchState,
     pub show_mode_state: ShowModeState,
+    pub dependency_tree_state: DependencyTreeState,
+    pub transactions_state: TransactionsState,
+    pub time_travel_state: TimeTravelState,
+    pub owner_search_state: OwnerSearchState,
+    pub removal_preview_state: RemovalPreviewState,
+    pub cache_cleanup_state: CacheCleanupState,
+    pub config_drift_state: ConfigDriftState,
+    pub downgrade_state: DowngradeState,
+    pub unowned_state: UnownedState,
 
     pub data_receiver: mpsc::Receiver<LoadedData>,
     pub is_loading: bool,
+    pub integrity_check: Option<IntegrityCheck>,
+    /// File owner index being built for the owner search
+    pub owner_index: Option<oneshot::Receiver<Arc<FileOwners>>>,
+    /// Unowned files search with the file owner index it used
+    pub unowned_scan: Option<oneshot::Receiver<(Arc<FileOwners>, UnownedFiles)>>,
+    /// Hashing of the backup files, which starts once the packages are listed
+    pub modified_configs_scan: Option<oneshot::Receiver<ModifiedConfigs>>,
+    pub source: Arc<dyn PackageSource>,
 }
 
 impl App {
-    pub fn new(rx: mpsc::Receiver<LoadedData>) -> Self {
+    pub fn new(rx: mpsc::Receiver<LoadedData>, source: Arc<dyn PackageSource>) -> Self {
         let state = AppState::new();
         let sort_state = SortState::new();
         let filter_state = FilterModalState::new(&state.all_tags, &state.all_repos);
@@ -77,6 +133,13 @@ impl App {
             config,
             output_log_area: Rect::default(), 
             package_list_area: Rect::default(),
+            info_scroll: 0,
+            info_tab: InfoTab::Info,
+            show_why_installed: false,
+            package_files: None,
+            marked_packages: BTreeSet::new(),
+            system_change: None,
+            dependency_filter: None,
             search_input: String::new(),
             search_cursor_position: 0,
             sort_state,
@@ -85,28 +148,49 @@ impl App {
             normal_state: NormalState,
             search_state: SearchState,
             show_mode_state,
+            dependency_tree_state: DependencyTreeState::new(),
+            transactions_state: TransactionsState::new(),
+            time_travel_state: TimeTravelState::default(),
+            owner_search_state: OwnerSearchState::default(),
+            removal_preview_state: RemovalPreviewState::default(),
+            cache_cleanup_state: CacheCleanupState::default(),
+            config_drift_state: ConfigDriftState::default(),
+            downgrade_state: DowngradeState::default(),
+            unowned_state: UnownedState::default(),
             action_state,
             data_receiver: rx,
             is_loading: true,
+            integrity_check: None,
+            owner_index: None,
+            unowned_scan: None,
+            modified_configs_scan: None,
+            source,
         }
     }
 
     pub fn execute_config_action(&mut self, action: &crate::config::Action) -> bool {
-        let (command_template, requires_package, show_mode_whitelist, show_mode_blacklist) =
-            match &action.action_type {
-                crate::config::ActionType::Command {
-                    command,
-                    requires_package,
-                    show_mode_whitelist,
-                    show_mode_blacklist,
-                } => (
-                    command,
-                    requires_package,
-                    show_mode_whitelist,
-                    show_mode_blacklist,
-                ),
-                _ => return false, 
-            };
+        let (
+            command_template,
+            requires_package,
+            show_mode_whitelist,
+            show_mode_blacklist,
+            preview_removal,
+        ) = match &action.action_type {
+            crate::config::ActionType::Command {
+                command,
+                requires_package,
+                show_mode_whitelist,
+                show_mode_blacklist,
+                preview_removal,
+            } => (
+                command,
+                requires_package,
+                show_mode_whitelist,
+                show_mode_blacklist,
+                preview_removal,
+            ),
+            _ => return false, 
+        };
 
         if !show_mode_whitelist.is_empty() {
             let current_mode_str = self.show_mode_state.active_show_mode.to_string();
@@ -135,10 +219,10 @@ impl App {
 
         let mut package_name: Option<String> = None;
         if *requires_package {
-            if let Some(selected_index) = self.selected_package.selected() {
-                if let Some(package) = self.state.filtered_packages.get(selected_index) {
-                    package_name = Some(package.name.clone());
-                }
+            if let Some(selected_index) = self.selected_package.selected()
+                && let Some(package) = self.state.filtered_packages.get(selected_index)
+            {
+                package_name = Some(package.name.clone());
             }
 
             if package_name.is_none() {
@@ -151,9 +235,37 @@ impl App {
             }
         }
 
-        let final_command =
-            crate::config::template_command(command_template, package_name.as_deref())
-                .unwrap_or_default();
+        let orphan_names: Vec<String> = if command_template.iter().any(|part| part == "{orphans}") {
+            if self.state.orphans.is_empty() {
+                self.output.info("No orphan packages to remove.".to_string());
+                self.input_mode = InputMode::Normal;
+                return false;
+            }
+            self.state.orphans.names().map(str::to_string).collect()
+        } else {
+            Vec::new()
+        };
+
+        let final_command = crate::config::template_command(
+            command_template,
+            package_name.as_deref(),
+            &orphan_names,
+        )
+        .unwrap_or_default();
+
+        if *preview_removal && is_removal_command(command_template) {
+            let targets = package_name.iter().chain(&orphan_names).map(String::as_str);
+            let impact =
+                RemovalImpact::compute(targets, &self.state.packages, &self.state.dependency_graph);
+            let title = match &package_name {
+                Some(name) => format!("{}: {}", action.name, name),
+                None => action.name.clone(),
+            };
+            self.removal_preview_state =
+                RemovalPreviewState::preview(title, impact, Some(final_command));
+            self.input_mode = InputMode::RemovalPreview;
+            return false;
+        }
 
         self.command_to_run = Some(final_command);
         true 
@@ -166,23 +278,41 @@ impl App {
 
         loop {
 
-            if self.is_loading {
+            if self.is_loading
+                && let Ok(loaded_data) = self.data_receiver.try_recv()
+            {
+                self.state.packages = loaded_data.packages;
+                self.state.available_packages = loaded_data.available_packages;
+                self.state.all_repos = loaded_data.all_repos;
+                self.state.orphans = loaded_data.orphans;
+                self.state.dependency_graph = loaded_data.dependency_graph;
+                self.state.pacman_log = loaded_data.pacman_log;
+                self.dependency_tree_state.rebuild(&self.state.dependency_graph);
+                for warning in loaded_data.warnings {
+                    self.output.warn(warning);
+                }
 
-                if let Ok(loaded_data) = self.data_receiver.try_recv() {
+                self.reload_tags(); 
 
-                    self.state.packages = loaded_data.packages;
-                    self.state.available_packages = loaded_data.available_packages;
-                    self.state.all_repos = loaded_data.all_repos;
-                    self.state.orphan_package_names = loaded_data.orphan_package_names;
+                self.filter_state = FilterModalState::new(&self.state.all_tags, &self.state.all_repos);
+                self.tag_state = TagModalState::new(&self.state.all_tags);
 
-                    self.reload_tags(); 
+                self.is_loading = false;
+                self.apply_filters(); 
+                self.start_modified_configs_scan();
+            }
 
-                    self.filter_state = FilterModalState::new(&self.state.all_tags, &self.state.all_repos);
-                    self.tag_state = TagModalState::new(&self.state.all_tags);
+            self.poll_modified_configs_scan();
+            self.poll_owner_index();
+            self.poll_unowned_scan();
+            self.poll_integrity_check();
 
-                    self.is_loading = false;
-                    self.apply_filters(); 
-                }
+            if self.info_tab == InfoTab::Files {
+                self.load_package_files();
+            }
+
+            if let Some(change) = self.system_change.take() {
+                self.run_system_change(terminal, change)?;
             }
 
             terminal.draw(|f| ui::ui(f, self))?;
@@ -196,10 +326,42 @@ impl App {
 
     pub fn apply_filters(&mut self) {
 
-        let source_list = if self.show_mode_state.active_show_mode == ShowMode::AllAvailable {
-            &self.state.available_packages
-        } else {
-            &self.state.packages
+        let mode_packages;
+        let source_list = match self.show_mode_state.active_show_mode {
+            ShowMode::PackageCache => {
+                mode_packages = self
+                    .state
+                    .package_cache
+                    .as_ref()
+                    .map(|cache| cache.packages(&self.state.packages, &self.state.available_packages))
+                    .unwrap_or_default();
+                &mode_packages
+            }
+            ShowMode::ConfigDrift => {
+                mode_packages = self
+                    .state
+                    .config_drift
+                    .as_ref()
+                    .map(|drift| drift.packages(&self.state.packages))
+                    .unwrap_or_default();
+                &mode_packages
+            }
+            ShowMode::ModifiedConfigs => {
+                mode_packages = self
+                    .state
+                    .packages
+                    .iter()
+                    .filter(|p| self.state.modified_configs.is_modified(&p.name))
+                    .cloned()
+                    .collect();
+                &mode_packages
+            }
+            ShowMode::AllAvailable => &self.state.available_packages,
+            ShowMode::TimeTravel => match &self.state.time_travel {
+                Some(time_travel) => &time_travel.packages,
+                None => &Vec::new(),
+            },
+            _ => &self.state.packages,
         };
 
         self.state.filtered_packages = backend::filter_packages(
@@ -207,7 +369,9 @@ impl App {
             &self.filter_state.tag_filters,
             &self.filter_state.repo_filters,
             self.show_mode_state.active_show_mode,
-            &self.state.orphan_package_names,
+            &self.state.orphans,
+            self.dependency_filter.as_ref(),
+            &self.state.dependency_graph,
         );
 
         if !self.search_input.is_empty() {
@@ -221,9 +385,9 @@ impl App {
         }
         self.sort_packages();
         if !self.state.filtered_packages.is_empty() {
-            self.selected_package.select(Some(0));
+            self.select_package(Some(0));
         } else {
-            self.selected_package.select(None);
+            self.select_package(None);
         }
     }
 
@@ -234,6 +398,12 @@ impl App {
         );
     }
 
+    // Selects a package and scrolls its information back to the top
+    fn select_package(&mut self, index: Option<usize>) {
+        self.selected_package.select(index);
+        self.info_scroll = 0;
+    }
+
     pub fn select_previous_package(&mut self) {
         let i = match self.selected_package.selected() {
             Some(i) => {
@@ -245,7 +415,7 @@ impl App {
             }
             None => 0,
         };
-        self.selected_package.select(Some(i));
+        self.select_package(Some(i));
     }
 
     pub fn select_next_package(&mut self) {
@@ -259,11 +429,717 @@ impl App {
             }
             None => 0,
         };
-        self.selected_package.select(Some(i));
+        self.select_package(Some(i));
+    }
+
+    /// Marks or unmarks the selected package and moves on to the next one.
+    pub fn toggle_mark(&mut self) {
+        let Some(package) = self
+            .selected_package
+            .selected()
+            .and_then(|i| self.state.filtered_packages.get(i))
+        else {
+            return;
+        };
+        if !self.marked_packages.remove(&package.name) {
+            self.marked_packages.insert(package.name.clone());
+        }
+        self.select_next_package();
+    }
+
+    /// Marks the marked packages, or the selected one if none are marked, as
+    /// explicitly installed or as dependencies. Packages that already have
+    /// the requested reason are skipped.
+    pub fn request_install_reason(&mut self, explicit: bool) {
+        let candidates: Vec<String> = if self.marked_packages.is_empty() {
+            self.selected_package
+                .selected()
+                .and_then(|i| self.state.filtered_packages.get(i))
+                .map(|p| vec![p.name.clone()])
+                .unwrap_or_default()
+        } else {
+            self.marked_packages.iter().cloned().collect()
+        };
+        let names: Vec<String> = candidates
+            .into_iter()
+            .filter(|name| {
+                self.state
+                    .packages
+                    .iter()
+                    .any(|p| p.name == *name && p.is_explicit != explicit)
+            })
+            .collect();
+
+        if names.is_empty() {
+            self.output.warn(format!(
+                "No installed packages to mark as {}.",
+                install_reason(explicit)
+            ));
+            return;
+        }
+        let command = self.source.install_reason_command(&names, explicit);
+        self.change_system(command, move |app| app.change_install_reason(names, explicit));
+    }
+
+    /// Deletes the given package cache files.
+    pub fn clean_package_cache(&mut self, files: Vec<CachedFile>) {
+        let paths: Vec<&Path> = files.iter().flat_map(CachedFile::paths).collect();
+        let command = self.source.remove_files_command(&paths);
+        self.change_system(command, move |app| app.forget_cached_files(files));
+    }
+
+    /// Resolves a `.pacnew` or `.pacsave` file, or opens it in the merge tool.
+    pub fn resolve_config_drift(&mut self, file: DriftFile, action: DriftAction) {
+        let command = file.resolve_command(self.source.as_ref(), action);
+        if command.is_none() && action == DriftAction::Merge {
+            self.output.warn("The package source is read-only, nothing to merge.".to_string());
+            return;
+        }
+        self.change_system(command, move |app| app.forget_config_drift(file, action));
+    }
+
+    /// Runs a command changing the system with the TUI suspended before the
+    /// next frame, then lets `apply` update the loaded state in memory
+    /// instead of reloading everything. Read-only sources have no command;
+    /// the change is then made in memory only.
+    pub fn change_system(
+        &mut self,
+        command: Option<Vec<String>>,
+        apply: impl FnOnce(&mut Self) + 'static,
+    ) {
+        match command {
+            Some(command) => {
+                self.system_change = Some(SystemChange {
+                    command,
+                    apply: Box::new(apply),
+                })
+            }
+            None => apply(self),
+        }
+    }
+
+    // Hands the terminal back to the shell while the command runs
+    fn run_system_change(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        change: SystemChange,
+    ) -> std::io::Result<()> {
+        let command = change.command;
+        restore_terminal()?;
+        let status = std::process::Command::new(&command[0]).args(&command[1..]).status();
+        *terminal = init_terminal()?;
+        match status {
+            Ok(status) if status.success() => (change.apply)(self),
+            Ok(status) => self.output.error(format!("'{}' failed: {}", command.join(" "), status)),
+            Err(e) => self.output.error(format!("Failed to run '{}': {}", command.join(" "), e)),
+        }
+        Ok(())
+    }
+
+    fn change_install_reason(&mut self, names: Vec<String>, explicit: bool) {
+        let installed = self.state.packages.iter_mut();
+        let available = self.state.available_packages.iter_mut().filter(|p| p.is_installed);
+        for package in installed.chain(available).filter(|p| names.contains(&p.name)) {
+            package.is_explicit = explicit;
+        }
+        self.state.orphans = Orphans::find(&self.state.packages, &self.state.dependency_graph);
+
+        self.output.info(format!(
+            "Marked {} package(s) as {}: {}",
+            names.len(),
+            install_reason(explicit),
+            names.join(", ")
+        ));
+        self.marked_packages.clear();
+        self.refresh_list();
+    }
+
+    fn forget_cached_files(&mut self, files: Vec<CachedFile>) {
+        let freed: u64 = files.iter().map(|f| f.size).sum();
+        if let Some(cache) = &mut self.state.package_cache {
+            let paths: Vec<PathBuf> = files.into_iter().map(|f| f.path).collect();
+            self.output.info(format!(
+                "Removed {} cached package file(s), freeing {:.2} MiB.",
+                paths.len(),
+                bytes_to_mib(freed)
+            ));
+            cache.remove(&paths);
+        }
+        self.refresh_list();
+    }
+
+    fn forget_config_drift(&mut self, file: DriftFile, action: DriftAction) {
+        let (path, drift_path) = (format!("/{}", file.path), format!("/{}", file.drift_path()));
+        let (message, replaced) = match (action, file.kind) {
+            (DriftAction::Merge, _) => {
+                // The copy stays until one of the versions is kept
+                if let Some(drift) = &mut self.state.config_drift {
+                    drift.refresh(&self.source.root(), &file);
+                }
+                (format!("Merged {} and {}.", path, drift_path), true)
+            }
+            (DriftAction::KeepOld, DriftKind::Pacnew) => {
+                (format!("Kept {}, removed {}.", path, drift_path), false)
+            }
+            (DriftAction::TakeNew, DriftKind::Pacnew) => {
+                (format!("Replaced {} with {}.", path, drift_path), true)
+            }
+            (DriftAction::KeepOld, DriftKind::Pacsave(_)) => {
+                (format!("Restored {} from {}.", path, drift_path), true)
+            }
+            (DriftAction::TakeNew, DriftKind::Pacsave(_)) => (format!("Removed {}.", drift_path), false),
+        };
+        self.output.info(message);
+        if action != DriftAction::Merge
+            && let Some(drift) = &mut self.state.config_drift
+        {
+            drift.remove(&file);
+        }
+        // Only a replaced or merged config file can have changed its checksum status
+        if replaced
+            && let Some(pkg) = file
+                .owner
+                .as_ref()
+                .and_then(|owner| self.state.packages.iter().find(|p| &p.name == owner))
+        {
+            self.state
+                .modified_configs
+                .recheck(self.source.as_ref(), pkg, &file.path);
+        }
+        self.refresh_list();
+    }
+
+    // Applies the filters again, keeping the selected package if it is still listed
+    fn refresh_list(&mut self) {
+        let selected = self
+            .selected_package
+            .selected()
+            .and_then(|i| self.state.filtered_packages.get(i))
+            .map(|p| p.name.clone());
+        self.apply_filters();
+        if let Some(index) = selected
+            .and_then(|name| self.state.filtered_packages.iter().position(|p| p.name == name))
+        {
+            self.select_package(Some(index));
+        }
+    }
+
+    /// Restricts the list to the dependency tree of the selected package.
+    pub fn set_dependency_filter(&mut self, required_by: bool) {
+        let Some(package) = self
+            .selected_package
+            .selected()
+            .and_then(|i| self.state.filtered_packages.get(i))
+        else {
+            self.output.warn("No package selected.".to_string());
+            return;
+        };
+
+        let name = package.name.clone();
+        let filter = if required_by {
+            backend::DependencyFilter::RequiredBy(name.clone())
+        } else {
+            backend::DependencyFilter::DependenciesOf(name.clone())
+        };
+        self.output.info(if required_by {
+            format!("Showing packages that need '{}'. Press Esc to clear.", name)
+        } else {
+            format!("Showing packages pulled in by '{}'. Press Esc to clear.", name)
+        });
+        self.dependency_filter = Some(filter);
+        self.apply_filters();
+    }
+
+    /// Shows what removing the selected package would free, without
+    /// running anything.
+    pub fn open_removal_preview(&mut self) {
+        let Some(package) = self
+            .selected_package
+            .selected()
+            .and_then(|i| self.state.filtered_packages.get(i))
+        else {
+            self.output.warn("No package selected.".to_string());
+            return;
+        };
+
+        let name = package.name.clone();
+        let impact = RemovalImpact::compute(
+            [name.as_str()],
+            &self.state.packages,
+            &self.state.dependency_graph,
+        );
+        self.removal_preview_state =
+            RemovalPreviewState::preview(format!("Removal Impact: {}", name), impact, None);
+        self.input_mode = InputMode::RemovalPreview;
+    }
+
+    /// Asks for a tag, then shows what removing everything carrying it would free.
+    pub fn open_tag_removal_preview(&mut self) {
+        if self.state.all_tags.is_empty() {
+            self.output.warn("No tags defined.".to_string());
+            return;
+        }
+        self.removal_preview_state = RemovalPreviewState::pick_tag();
+        self.input_mode = InputMode::RemovalPreview;
+    }
+
+    pub fn clear_dependency_filter(&mut self) {
+        if self.dependency_filter.take().is_some() {
+            self.apply_filters();
+        }
+    }
+
+    /// Selects the named package in the main list, clearing the search and
+    /// dependency filter first if they hide it.
+    pub fn jump_to_package(&mut self, name: &str) {
+        let position = |app: &Self| {
+            app.state
+                .filtered_packages
+                .iter()
+                .position(|p| p.name == name)
+        };
+
+        if position(self).is_none()
+            && (!self.search_input.is_empty() || self.dependency_filter.is_some())
+        {
+            self.search_input.clear();
+            self.search_cursor_position = 0;
+            self.dependency_filter = None;
+            self.apply_filters();
+        }
+
+        match position(self) {
+            Some(index) => self.select_package(Some(index)),
+            None => self
+                .output
+                .warn(format!("'{}' is not shown in the current view.", name)),
+        }
+    }
+
+    pub fn open_transactions(&mut self) {
+        if self.state.pacman_log.transactions.is_empty() {
+            self.output.warn("No transactions found in the pacman log.".to_string());
+        } else {
+            self.input_mode = InputMode::Transactions;
+            self.transactions_state.selection.select(Some(0));
+        }
+    }
+
+    // Reads the file list of the selected package unless it is already loaded
+    fn load_package_files(&mut self) {
+        let Some(package) = self
+            .selected_package
+            .selected()
+            .and_then(|i| self.state.filtered_packages.get(i))
+        else {
+            self.package_files = None;
+            return;
+        };
+        if self.package_files.as_ref().is_some_and(|(name, _)| *name == package.name) {
+            return;
+        }
+        let files = if package.is_installed {
+            self.source
+                .package_files(&package.name, &package.version)
+                .map(|files| file_statuses(&self.source.root(), &files.files))
+                .map_err(|e| e.to_string())
+        } else {
+            Err("Not installed".to_string())
+        };
+        self.package_files = Some((package.name.clone(), files));
+    }
+
+    pub fn toggle_why_installed(&mut self) {
+        self.show_why_installed = !self.show_why_installed;
+        self.info_tab = InfoTab::Info;
+        self.info_scroll = 0;
+    }
+
+    pub fn toggle_info_tab(&mut self) {
+        self.info_tab = match self.info_tab {
+            InfoTab::Info => InfoTab::Files,
+            InfoTab::Files => InfoTab::Info,
+        };
+        self.info_scroll = 0;
+    }
+
+    /// Opens the owner search, building the file owner index on a blocking
+    /// task on first use. The modal shows that it is indexing meanwhile.
+    pub fn open_owner_search(&mut self) {
+        self.input_mode = InputMode::OwnerSearch;
+        self.owner_search_state.input.clear();
+        self.owner_search_state.results.clear();
+        self.owner_search_state.selection.select(None);
+        if self.state.file_owners.is_some() || self.owner_index.is_some() {
+            return;
+        }
+        let source = Arc::clone(&self.source);
+        let packages: Vec<Package> =
+            self.state.packages.iter().filter(|p| p.is_installed).cloned().collect();
+        let (tx, receiver) = oneshot::channel();
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.send(Arc::new(FileOwners::build(source.as_ref(), &packages)));
+        });
+        self.owner_index = Some(receiver);
+    }
+
+    // Keeps the file owner index once it is built and searches it for what
+    // was typed in the meantime
+    fn poll_owner_index(&mut self) {
+        let Some(receiver) = &mut self.owner_index else {
+            return;
+        };
+        let owners = match receiver.try_recv() {
+            Ok(owners) => Some(owners),
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Err(oneshot::error::TryRecvError::Closed) => None,
+        };
+        self.owner_index = None;
+        let Some(owners) = owners else {
+            self.output.error("Indexing the installed files failed.".to_string());
+            if matches!(self.input_mode, InputMode::OwnerSearch) {
+                self.input_mode = InputMode::Normal;
+            }
+            return;
+        };
+        if self.state.file_owners.is_none() {
+            report_file_owners(&mut self.output, &owners);
+            self.state.file_owners = Some(owners);
+        }
+        self.owner_search_state.update_results(self.state.file_owners.as_deref());
+    }
+
+    /// Searches the configured roots for files no installed package owns on a
+    /// blocking task, building the file owner index first unless it is cached.
+    pub fn open_unowned_files(&mut self) {
+        self.input_mode = InputMode::Unowned;
+        if self.unowned_scan.is_some() {
+            // The modal shows the running scan
+            return;
+        }
+        let root = self.source.root();
+        let config = self.config.unowned.clone();
+        let source = Arc::clone(&self.source);
+        let cached = self.state.file_owners.clone();
+        let packages: Vec<Package> = if cached.is_none() {
+            self.state.packages.iter().filter(|p| p.is_installed).cloned().collect()
+        } else {
+            Vec::new()
+        };
+        let (tx, receiver) = oneshot::channel();
+        tokio::task::spawn_blocking(move || {
+            let owners = cached
+                .unwrap_or_else(|| Arc::new(FileOwners::build(source.as_ref(), &packages)));
+            let files = UnownedFiles::scan(&root, &owners, &config);
+            let _ = tx.send((owners, files));
+        });
+        self.unowned_scan = Some(receiver);
+        self.unowned_state = UnownedState::scanning();
+    }
+
+    // Shows the unowned files once the scan is done and keeps its owner index
+    fn poll_unowned_scan(&mut self) {
+        let Some(receiver) = &mut self.unowned_scan else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Err(oneshot::error::TryRecvError::Closed) => None,
+        };
+        self.unowned_scan = None;
+        let Some((owners, files)) = result else {
+            self.output.error("Searching for unowned files failed.".to_string());
+            self.unowned_state = UnownedState::default();
+            return;
+        };
+        if self.state.file_owners.is_none() {
+            report_file_owners(&mut self.output, &owners);
+            self.state.file_owners = Some(owners);
+        }
+        self.output.info(format!(
+            "Found {} unowned path(s), {:.2} MiB.",
+            files.path_count(),
+            bytes_to_mib(files.total_size())
+        ));
+        self.unowned_state = UnownedState::new(files);
+    }
+
+    /// Reads the package cache directories again.
+    pub fn scan_package_cache(&mut self) {
+        match PackageCache::scan(&self.source.cache_dirs()) {
+            Ok(cache) => {
+                self.output.info(format!(
+                    "Package cache: {} file(s), {:.2} MiB.",
+                    cache.file_count(),
+                    bytes_to_mib(cache.total_size())
+                ));
+                self.state.package_cache = Some(cache);
+            }
+            Err(e) => self.output.error(format!("Failed to read the package cache: {}", e)),
+        }
+    }
+
+    pub fn open_cache_cleanup(&mut self) {
+        self.scan_package_cache();
+        if self.state.package_cache.is_some() {
+            self.cache_cleanup_state =
+                CacheCleanupState::new(self.state.package_cache.as_ref(), &self.state.packages);
+            self.input_mode = InputMode::CacheCleanup;
+        }
+    }
+
+    /// Lists the cached versions of the selected package to install one of them.
+    pub fn open_downgrade(&mut self) {
+        let Some(name) = self
+            .selected_package
+            .selected()
+            .and_then(|i| self.state.filtered_packages.get(i))
+            .map(|p| p.name.clone())
+        else {
+            self.output.warn("No package selected.".to_string());
+            return;
+        };
+        self.scan_package_cache();
+        let Some(cache) = &self.state.package_cache else {
+            return;
+        };
+        let versions: Vec<CachedVersion> = cache
+            .versions(&name)
+            .iter()
+            .map(|file| CachedVersion {
+                desc: read_package_desc(&file.path).map_err(|e| e.to_string()),
+                file: file.clone(),
+            })
+            .collect();
+        if versions.is_empty() {
+            self.output.warn(format!("No cached versions of {}.", name));
+            return;
+        }
+
+        let installed_version = self
+            .state
+            .packages
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.version.clone());
+        self.downgrade_state = DowngradeState::new(name, installed_version, versions);
+        self.input_mode = InputMode::Downgrade;
+    }
+
+    // Hashes the backup files of the installed packages on a blocking task,
+    // so a large /etc does not delay the package list
+    fn start_modified_configs_scan(&mut self) {
+        let (tx, receiver) = oneshot::channel();
+        let source = Arc::clone(&self.source);
+        let packages: Vec<Package> =
+            self.state.packages.iter().filter(|p| p.is_installed).cloned().collect();
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.send(ModifiedConfigs::scan(source.as_ref(), &packages));
+        });
+        self.modified_configs_scan = Some(receiver);
+    }
+
+    fn poll_modified_configs_scan(&mut self) {
+        let Some(receiver) = &mut self.modified_configs_scan else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(modified_configs) => self.state.modified_configs = modified_configs,
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Err(oneshot::error::TryRecvError::Closed) => {
+                self.output.error("Checking the config files failed.".to_string());
+            }
+        }
+        self.modified_configs_scan = None;
+        if self.show_mode_state.active_show_mode == ShowMode::ModifiedConfigs {
+            self.refresh_list();
+        }
+    }
+
+    /// Checks the files of all installed packages against their mtree on a
+    /// background task. Results are attached to the packages as they arrive.
+    pub fn start_integrity_check(&mut self) {
+        if self.integrity_check.is_some() {
+            self.output.warn("An integrity check is already running.".to_string());
+            return;
+        }
+        let targets: Vec<(String, String)> = self
+            .state
+            .packages
+            .iter()
+            .filter(|p| p.is_installed)
+            .map(|p| (p.name.clone(), p.version.clone()))
+            .collect();
+        if targets.is_empty() {
+            self.output.warn("No installed packages to check.".to_string());
+            return;
+        }
+
+        let (tx, receiver) = mpsc::unbounded_channel();
+        let source = Arc::clone(&self.source);
+        let total = targets.len();
+        tokio::task::spawn_blocking(move || {
+            for (name, version) in targets {
+                let result = integrity::check_package(source.as_ref(), &name, &version)
+                    .map_err(|e| e.to_string());
+                // The receiver is gone once the app closes
+                if tx.send((name, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        self.output.info(format!("Checking the integrity of {} packages...", total));
+        self.integrity_check = Some(IntegrityCheck {
+            receiver,
+            checked: 0,
+            total,
+        });
+    }
+
+    // Attaches the integrity results that arrived since the last frame
+    fn poll_integrity_check(&mut self) {
+        let Some(check) = &mut self.integrity_check else {
+            return;
+        };
+        let mut results = Vec::new();
+        let finished = loop {
+            match check.receiver.try_recv() {
+                Ok(result) => results.push(result),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        if results.is_empty() && !finished {
+            return;
+        }
+        check.checked += results.len();
+
+        for (name, result) in results {
+            if let Some(package) = self.state.packages.iter_mut().find(|p| p.name == name) {
+                package.integrity = Some(result);
+            }
+        }
+        if finished {
+            self.integrity_check = None;
+            let damaged = self.state.packages.iter().filter(|p| p.has_integrity_problems()).count();
+            let unchecked = self
+                .state
+                .packages
+                .iter()
+                .filter(|p| matches!(p.integrity, Some(Err(_))))
+                .count();
+            self.output.info(format!(
+                "Integrity check finished: {} package(s) with problems, {} without a readable mtree.",
+                damaged, unchecked
+            ));
+        }
+        // Re-filtering every frame is only worth it while the problems are listed
+        if finished || self.show_mode_state.active_show_mode == ShowMode::IntegrityProblems {
+            self.refresh_list();
+        }
+    }
+
+    /// Looks for `.pacnew` and `.pacsave` files again.
+    pub fn scan_config_drift(&mut self) {
+        let drift = ConfigDrift::scan(self.source.as_ref(), &self.state.packages);
+        self.output.info(format!(
+            "Config drift: {} .pacnew/.pacsave file(s).",
+            drift.files().len()
+        ));
+        self.state.config_drift = Some(drift);
+    }
+
+    /// Offers to resolve the `.pacnew` and `.pacsave` files of the selected package.
+    pub fn open_config_drift(&mut self) {
+        let Some(name) = self
+            .selected_package
+            .selected()
+            .and_then(|i| self.state.filtered_packages.get(i))
+            .map(|p| p.name.clone())
+        else {
+            self.output.warn("No package selected.".to_string());
+            return;
+        };
+        if self.state.config_drift.is_none() {
+            self.scan_config_drift();
+        }
+        let files: Vec<DriftFile> = self
+            .state
+            .config_drift
+            .as_ref()
+            .map(|drift| drift.for_entry(&name).cloned().collect())
+            .unwrap_or_default();
+        if files.is_empty() {
+            self.output.info(format!("No .pacnew or .pacsave files for {}.", name));
+            return;
+        }
+        self.config_drift_state = ConfigDriftState::new(files);
+        self.input_mode = InputMode::ConfigDrift;
+    }
+
+    pub fn open_time_travel(&mut self) {
+        if self.state.pacman_log.transactions.is_empty() {
+            self.output.warn("No transactions found in the pacman log.".to_string());
+            return;
+        }
+        self.input_mode = InputMode::TimeTravel;
+        self.time_travel_state.input.clear();
+    }
+
+    /// Shows the packages installed at `moment`, as far as the pacman log knows.
+    pub fn time_travel_to(&mut self, moment: DateTime<Utc>) {
+        let transaction = self.state.pacman_log.last_transaction_before(moment);
+        self.set_time_travel(transaction, moment);
+    }
+
+    /// Shows the packages installed right after a logged transaction.
+    pub fn time_travel_to_transaction(&mut self, transaction: usize) {
+        let Some(started) = self.state.pacman_log.transactions.get(transaction).map(|t| t.started)
+        else {
+            return;
+        };
+        self.set_time_travel(Some(transaction), started);
+    }
+
+    fn set_time_travel(&mut self, transaction: Option<usize>, moment: DateTime<Utc>) {
+        let time_travel = TimeTravel::build(
+            &self.state.pacman_log,
+            transaction,
+            moment,
+            &self.state.packages,
+            &self.state.available_packages,
+        );
+        self.output.info(format!(
+            "Time travel to {}: {} packages installed, {} changed since.",
+            moment.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            time_travel.installed_count(),
+            time_travel.changed_count()
+        ));
+        self.state.time_travel = Some(time_travel);
+        self.show_mode_state.active_show_mode = ShowMode::TimeTravel;
+        self.apply_filters();
     }
 
     pub fn reload_tags(&mut self) {
         self.state.all_tags = db::get_all_tags().unwrap_or_default();
         self.tag_state.update_filtered_tags(&self.state.all_tags);
     }
-}
\ No newline at end of file
+}
+
+fn install_reason(explicit: bool) -> &'static str {
+    if explicit { "explicitly installed" } else { "dependencies" }
+}
+
+// Whether a command removes packages, like `pacman -Rns` or `yay -R`
+fn is_removal_command(command: &[String]) -> bool {
+    command.iter().any(|arg| arg == "--remove" || arg.starts_with("-R"))
+}
+
+fn report_file_owners(output: &mut OutputLog, owners: &FileOwners) {
+    if owners.is_empty() {
+        output.warn("No package file lists could be read.".to_string());
+    } else {
+        output.info(format!("Indexed {} installed paths.", owners.len()));
+    }
+}
@@ -43,6 +43,21 @@ impl ActionModalState {
         actions.push(Action::new_local("Add Tag", 'a', false));
         actions.push(Action::new_local("Remove Tag", 'd', false));
         actions.push(Action::new_local("Clear Output", 'c', false));
+        actions.push(Action::new_local("Show Dependencies", 'D', true));
+        actions.push(Action::new_local("Show Required By", 'R', true));
+        actions.push(Action::new_local("Recent Transactions", 'L', true));
+        actions.push(Action::new_local("Time Travel", 'T', true));
+        actions.push(Action::new_local("Find File Owner", 'O', true));
+        actions.push(Action::new_local("Why Installed", 'w', false));
+        actions.push(Action::new_local("Removal Impact", 'x', false));
+        actions.push(Action::new_local("Clean Package Cache", 'C', true));
+        actions.push(Action::new_local("Downgrade Package", 'V', true));
+        actions.push(Action::new_local("Resolve Config Drift", 'M', true));
+        actions.push(Action::new_local("Check Integrity", 'I', true));
+        actions.push(Action::new_local("Find Unowned Files", 'U', true));
+        actions.push(Action::new_local("Mark as Explicitly Installed", 'e', false));
+        actions.push(Action::new_local("Mark as Dependency", 'E', true));
+        actions.push(Action::new_local("Removal Impact of Tag", 'X', true));
 
         self.all_actions = actions;
         self.update_filtered_options();
@@ -98,64 +113,128 @@ impl ActionModalState {
     }
 
     fn on_enter(&mut self, app: &mut App) -> bool {
-        if let Some(selected_index) = self.selection.selected() {
+        if let Some(selected_index) = self.selection.selected()
+            && let Some(action) = self.filtered_options.get(selected_index).cloned()
+        {
+            match &action.action_type {
 
-            if let Some(action) = self.filtered_options.get(selected_index).cloned() {
-                match &action.action_type {
+                crate::config::ActionType::Local => {
+                    match action.name.as_str() {
+                        "Add Tag" => {
+                            app.input_mode = InputMode::Tagging;
+                            app.tag_state.update_filtered_tags(&app.state.all_tags);
+                            app.tag_state.selection.select(Some(0));
+                            app.tag_state.input.clear();
+                            app.tag_state.focus = TagModalFocus::Input;
+                            return false; 
+                        }
+                        "Remove Tag" => {
+                            let package_tags =
+                                if let Some(pkg_idx) = app.selected_package.selected() {
+                                    app.state
+                                        .filtered_packages
+                                        .get(pkg_idx)
+                                        .map(|p| p.tags.clone())
+                                        .unwrap_or_default()
+                                } else {
+                                    Vec::new()
+                                };
 
-                    crate::config::ActionType::Local => {
-                        match action.name.as_str() {
-                            "Add Tag" => {
-                                app.input_mode = InputMode::Tagging;
-                                app.tag_state.update_filtered_tags(&app.state.all_tags);
+                            if !package_tags.is_empty() {
+                                app.input_mode = InputMode::Untagging;
+                                app.tag_state.update_filtered_tags(&package_tags);
                                 app.tag_state.selection.select(Some(0));
                                 app.tag_state.input.clear();
                                 app.tag_state.focus = TagModalFocus::Input;
-                                return false; 
-                            }
-                            "Remove Tag" => {
-                                let package_tags =
-                                    if let Some(pkg_idx) = app.selected_package.selected() {
-                                        app.state
-                                            .filtered_packages
-                                            .get(pkg_idx)
-                                            .map(|p| p.tags.clone())
-                                            .unwrap_or_default()
-                                    } else {
-                                        Vec::new()
-                                    };
-
-                                if !package_tags.is_empty() {
-                                    app.input_mode = InputMode::Untagging;
-                                    app.tag_state.update_filtered_tags(&package_tags);
-                                    app.tag_state.selection.select(Some(0));
-                                    app.tag_state.input.clear();
-                                    app.tag_state.focus = TagModalFocus::Input;
-                                } else {
-                                    app.output.warn(
-                                        "Selected package has no tags to remove.".to_string(),
-                                    );
-                                    app.input_mode = InputMode::Normal;
-                                }
-                                return false; 
-                            }
-                            "Clear Output" => {
-                                app.output.clear();
-                                app.output.info("Output cleared.".to_string());
-                                app.input_mode = InputMode::Normal;
-                                return false;
-                            }
-                            _ => {
+                            } else {
+                                app.output.warn(
+                                    "Selected package has no tags to remove.".to_string(),
+                                );
                                 app.input_mode = InputMode::Normal;
-                                return false;
                             }
+                            return false; 
+                        }
+                        "Clear Output" => {
+                            app.output.clear();
+                            app.output.info("Output cleared.".to_string());
+                            app.input_mode = InputMode::Normal;
+                            return false;
+                        }
+                        "Show Dependencies" | "Show Required By" => {
+                            app.input_mode = InputMode::Normal;
+                            app.set_dependency_filter(action.name == "Show Required By");
+                            return false;
+                        }
+                        "Find File Owner" => {
+                            app.input_mode = InputMode::Normal;
+                            app.open_owner_search();
+                            return false;
+                        }
+                        "Mark as Explicitly Installed" | "Mark as Dependency" => {
+                            app.input_mode = InputMode::Normal;
+                            app.request_install_reason(action.name == "Mark as Explicitly Installed");
+                            return false;
+                        }
+                        "Why Installed" => {
+                            app.input_mode = InputMode::Normal;
+                            app.toggle_why_installed();
+                            return false;
+                        }
+                        "Clean Package Cache" => {
+                            app.input_mode = InputMode::Normal;
+                            app.open_cache_cleanup();
+                            return false;
+                        }
+                        "Check Integrity" => {
+                            app.input_mode = InputMode::Normal;
+                            app.start_integrity_check();
+                            return false;
+                        }
+                        "Find Unowned Files" => {
+                            app.input_mode = InputMode::Normal;
+                            app.open_unowned_files();
+                            return false;
+                        }
+                        "Resolve Config Drift" => {
+                            app.input_mode = InputMode::Normal;
+                            app.open_config_drift();
+                            return false;
+                        }
+                        "Downgrade Package" => {
+                            app.input_mode = InputMode::Normal;
+                            app.open_downgrade();
+                            return false;
+                        }
+                        "Removal Impact" => {
+                            app.input_mode = InputMode::Normal;
+                            app.open_removal_preview();
+                            return false;
+                        }
+                        "Removal Impact of Tag" => {
+                            app.input_mode = InputMode::Normal;
+                            app.open_tag_removal_preview();
+                            return false;
+                        }
+                        "Time Travel" => {
+                            app.input_mode = InputMode::Normal;
+                            app.open_time_travel();
+                            return false;
+                        }
+                        "Recent Transactions" => {
+                            app.input_mode = InputMode::Normal;
+                            app.open_transactions();
+                            return false;
+                        }
+                        _ => {
+                            app.input_mode = InputMode::Normal;
+                            return false;
                         }
                     }
+                }
 
-                    crate::config::ActionType::Command { .. } => {
+                crate::config::ActionType::Command { .. } => {
 
-                        return app.execute_config_action(&action);
-                    }
+                    return app.execute_config_action(&action);
                 }
             }
         }
@@ -226,11 +305,8 @@ impl KeyEventHandler for ActionModalState {
                 KeyCode::Tab => {
                     self.focus = ActionModalFocus::Input;
                 }
-                KeyCode::Enter => {
-
-                    if self.on_enter(app) {
-                        return Ok(true); 
-                    }
+                KeyCode::Enter if self.on_enter(app) => {
+                    return Ok(true); 
                 }
                 KeyCode::Esc | KeyCode::Char('q') => {
                     app.input_mode = InputMode::Normal;
@@ -1,7 +1,16 @@
 use crate::{
     db,
-    packages::{models::{Package}}, 
+    packages::{
+        cache::PackageCache,
+        config_drift::ConfigDrift,
+        deps::DependencyGraph, files::FileOwners, integrity::IntegrityReport, models::Package,
+        modified_configs::ModifiedConfigs, orphans::Orphans,
+        pacman_log::PacmanLog, time_travel::TimeTravel,
+    },
 };
+use crate::tui::app::App;
+use std::sync::Arc;
+use tokio::sync::mpsc;
 
 // --- Enums for application state ---
 
@@ -14,6 +23,22 @@ pub enum InputMode {
     Searching,
     Showing, 
     Action,
+    DependencyTree,
+    Transactions,
+    TimeTravel,
+    OwnerSearch,
+    RemovalPreview,
+    CacheCleanup,
+    Downgrade,
+    ConfigDrift,
+    Unowned,
+}
+
+/// What the package information pane shows
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum InfoTab {
+    Info,
+    Files,
 }
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ActionModalFocus{
@@ -31,12 +56,33 @@ pub enum TagModalFocus {
     Input,
     List,
 }
+
+/// A command changing the system, run with the TUI suspended so the
+/// privilege command can ask for a password, and the in-memory update made
+/// once it succeeds. See `App::change_system`.
+pub struct SystemChange {
+    pub command: Vec<String>,
+    pub apply: Box<dyn FnOnce(&mut App)>,
+}
+
+/// A running integrity check, see `App::start_integrity_check`
+pub struct IntegrityCheck {
+    /// Results per package as they are checked
+    pub receiver: mpsc::UnboundedReceiver<(String, Result<IntegrityReport, String>)>,
+    pub checked: usize,
+    pub total: usize,
+}
+
 // --- State Management Structs ---
 pub struct LoadedData {
     pub packages: Vec<Package>,
     pub available_packages: Vec<Package>, 
     pub all_repos: Vec<String>,
-    pub orphan_package_names: Vec<String>, 
+    pub orphans: Orphans,
+    pub dependency_graph: DependencyGraph,
+    pub pacman_log: PacmanLog,
+    /// Non-fatal problems hit while loading, shown in the output log
+    pub warnings: Vec<String>,
 }
 /// Holds the core data of the application
 pub struct AppState {
@@ -45,7 +91,17 @@ pub struct AppState {
     pub filtered_packages: Vec<Package>,
     pub all_tags: Vec<String>,
     pub all_repos: Vec<String>,
-    pub orphan_package_names: Vec<String>, 
+    pub orphans: Orphans,
+    pub dependency_graph: DependencyGraph,
+    pub pacman_log: PacmanLog,
+    pub modified_configs: ModifiedConfigs,
+    pub time_travel: Option<TimeTravel>,
+    /// Built on the first owner search, as it reads every file list
+    pub file_owners: Option<Arc<FileOwners>>,
+    /// Read when the package cache is first shown
+    pub package_cache: Option<PackageCache>,
+    /// Scanned when the config drift view is shown
+    pub config_drift: Option<ConfigDrift>,
 }
 
 impl AppState {
@@ -56,7 +112,14 @@ impl AppState {
             filtered_packages: Vec::new(),
             all_tags: db::get_all_tags().unwrap_or_default(),
             all_repos: Vec::new(),
-            orphan_package_names: Vec::new(), 
+            orphans: Orphans::default(),
+            dependency_graph: DependencyGraph::default(),
+            pacman_log: PacmanLog::default(),
+            modified_configs: ModifiedConfigs::default(),
+            time_travel: None,
+            file_owners: None,
+            package_cache: None,
+            config_drift: None,
         }
     }
 }
\ No newline at end of file
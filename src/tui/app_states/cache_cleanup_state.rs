@@ -0,0 +1,95 @@
+use crate::{
+    packages::{
+        cache::{CachedFile, PackageCache},
+        models::Package,
+    },
+    tui::{
+        app::App,
+        app_states::{app_state::InputMode, state::KeyEventHandler},
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use std::io;
+
+/// How many versions of each package a cleanup keeps unless told otherwise,
+/// the same as `paccache`
+const DEFAULT_KEEP: usize = 3;
+
+/// Manages the package cache cleanup prompt, which previews what keeping the
+/// given number of versions would delete.
+pub struct CacheCleanupState {
+    pub input: String,
+    /// What the cleanup would delete, updated when the input changes
+    pub candidates: Vec<CachedFile>,
+}
+
+impl CacheCleanupState {
+    pub fn new(cache: Option<&PackageCache>, installed: &[Package]) -> Self {
+        let mut state = Self::default();
+        state.update_candidates(cache, installed);
+        state
+    }
+
+    /// The number of versions to keep, if the input is a valid number
+    pub fn keep(&self) -> Option<usize> {
+        self.input.trim().parse().ok()
+    }
+
+    /// Total size of the files the cleanup would delete, in bytes
+    pub fn freed(&self) -> u64 {
+        self.candidates.iter().map(|f| f.size).sum()
+    }
+
+    fn update_candidates(&mut self, cache: Option<&PackageCache>, installed: &[Package]) {
+        self.candidates = match (cache, self.keep()) {
+            (Some(cache), Some(keep)) => cache
+                .cleanup_candidates(keep, installed)
+                .into_iter()
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        };
+    }
+}
+
+impl Default for CacheCleanupState {
+    fn default() -> Self {
+        Self {
+            input: DEFAULT_KEEP.to_string(),
+            candidates: Vec::new(),
+        }
+    }
+}
+
+impl KeyEventHandler for CacheCleanupState {
+    fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+        match key.code {
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                self.input.push(c);
+                self.update_candidates(app.state.package_cache.as_ref(), &app.state.packages);
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.update_candidates(app.state.package_cache.as_ref(), &app.state.packages);
+            }
+            KeyCode::Enter => {
+                if self.keep().is_none() {
+                    app.output.error("Enter how many versions to keep.".to_string());
+                    return Ok(false);
+                }
+                let files = std::mem::take(&mut self.candidates);
+                app.input_mode = InputMode::Normal;
+                if files.is_empty() {
+                    app.output.info("Nothing to clean up in the package cache.".to_string());
+                } else {
+                    app.clean_package_cache(files);
+                }
+            }
+            KeyCode::Esc => {
+                app.input_mode = InputMode::Normal;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
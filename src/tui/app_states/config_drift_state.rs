@@ -0,0 +1,107 @@
+use crate::{
+    packages::config_drift::{DriftAction, DriftFile, DriftKind},
+    tui::{
+        app::App,
+        app_states::{
+            app_state::InputMode,
+            state::KeyEventHandler,
+        },
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::ListState;
+use std::io;
+
+/// Manages the modal resolving the `.pacnew` and `.pacsave` files of the
+/// selected package, the way `pacdiff` does.
+#[derive(Default)]
+pub struct ConfigDriftState {
+    pub files: Vec<DriftFile>,
+    pub selection: ListState,
+    /// Action waiting for a y/N answer, as it overwrites or deletes a file
+    pub confirm: Option<DriftAction>,
+}
+
+impl ConfigDriftState {
+    pub fn new(files: Vec<DriftFile>) -> Self {
+        Self {
+            files,
+            selection: ListState::default().with_selected(Some(0)),
+            confirm: None,
+        }
+    }
+
+    fn selected_file(&self) -> Option<&DriftFile> {
+        self.selection.selected().and_then(|i| self.files.get(i))
+    }
+
+    /// The question asked before resolving the selected file, naming the
+    /// file that gets overwritten or deleted.
+    pub fn confirmation(&self) -> Option<String> {
+        let action = self.confirm?;
+        let file = self.selected_file()?;
+        let drift_path = file.drift_path();
+        Some(match (action, file.kind) {
+            (DriftAction::KeepOld, DriftKind::Pacnew) | (DriftAction::TakeNew, DriftKind::Pacsave(_)) => {
+                format!("Delete /{}? (y/N)", drift_path)
+            }
+            _ => format!("Overwrite /{} with /{}? (y/N)", file.path, drift_path),
+        })
+    }
+
+    fn select_previous(&mut self) {
+        if self.files.is_empty() {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) if i > 0 => i - 1,
+            _ => self.files.len() - 1,
+        };
+        self.selection.select(Some(i));
+    }
+
+    fn select_next(&mut self) {
+        if self.files.is_empty() {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) if i < self.files.len() - 1 => i + 1,
+            _ => 0,
+        };
+        self.selection.select(Some(i));
+    }
+
+    fn resolve(&mut self, app: &mut App, action: DriftAction) {
+        let Some(file) = self.selected_file() else {
+            return;
+        };
+        app.input_mode = InputMode::Normal;
+        app.resolve_config_drift(file.clone(), action);
+    }
+}
+
+impl KeyEventHandler for ConfigDriftState {
+    fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+        if let Some(action) = self.confirm.take() {
+            // Anything but `y` cancels
+            if key.code == KeyCode::Char('y') {
+                self.resolve(app, action);
+            }
+            return Ok(false);
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Char('o') if self.selected_file().is_some() => {
+                self.confirm = Some(DriftAction::KeepOld)
+            }
+            KeyCode::Char('n') if self.selected_file().is_some() => {
+                self.confirm = Some(DriftAction::TakeNew)
+            }
+            KeyCode::Char('m') => self.resolve(app, DriftAction::Merge),
+            KeyCode::Esc | KeyCode::Char('q') => app.input_mode = InputMode::Normal,
+            _ => {}
+        }
+        Ok(false)
+    }
+}
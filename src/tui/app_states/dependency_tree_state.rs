@@ -0,0 +1,218 @@
+use crate::{
+    packages::deps::DependencyGraph,
+    tui::{
+        app::App,
+        app_states::{app_state::InputMode, state::KeyEventHandler},
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::ListState;
+use std::collections::HashSet;
+use std::io;
+
+/// A visible line of the dependency tree.
+pub struct TreeRow {
+    pub name: String,
+    pub depth: usize,
+    pub has_children: bool,
+    pub expanded: bool,
+    /// The package already appears higher up on this branch
+    pub is_cycle: bool,
+    /// Names from the root down to this node, used to identify it
+    path: Vec<String>,
+}
+
+/// Manages the dependency tree shown below the package information,
+/// similar to `pactree` (or `pactree -r` in reverse mode).
+pub struct DependencyTreeState {
+    pub root: Option<String>,
+    pub reverse: bool,
+    pub rows: Vec<TreeRow>,
+    pub selection: ListState,
+    expanded: HashSet<Vec<String>>,
+}
+
+impl DependencyTreeState {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            reverse: false,
+            rows: Vec::new(),
+            selection: ListState::default(),
+            expanded: HashSet::new(),
+        }
+    }
+
+    /// Points the tree at a new package, resetting expansion if it changed.
+    pub fn set_root(&mut self, root: Option<&str>, graph: &DependencyGraph) {
+        if self.root.as_deref() == root {
+            return;
+        }
+        self.root = root.map(String::from);
+        self.expanded.clear();
+        if let Some(root) = root {
+            // The root starts expanded so its direct dependencies are visible
+            self.expanded.insert(vec![root.to_string()]);
+        }
+        self.rebuild(graph);
+        self.selection.select(if self.rows.is_empty() { None } else { Some(0) });
+    }
+
+    /// Recomputes the visible rows, e.g. after the graph was reloaded.
+    pub fn rebuild(&mut self, graph: &DependencyGraph) {
+        self.rows.clear();
+        if let Some(root) = self.root.clone() {
+            let mut path = Vec::new();
+            self.push_node(graph, &root, 0, &mut path);
+        }
+        if let Some(selected) = self.selection.selected()
+            && selected >= self.rows.len()
+        {
+            self.selection.select(self.rows.len().checked_sub(1));
+        }
+    }
+
+    fn push_node(&mut self, graph: &DependencyGraph, name: &str, depth: usize, path: &mut Vec<String>) {
+        let children = if self.reverse {
+            graph.required_by(name)
+        } else {
+            graph.depends_on(name)
+        };
+        let is_cycle = path.iter().any(|p| p == name);
+        path.push(name.to_string());
+        let expanded = !is_cycle && self.expanded.contains(path);
+
+        self.rows.push(TreeRow {
+            name: name.to_string(),
+            depth,
+            has_children: !is_cycle && !children.is_empty(),
+            expanded,
+            is_cycle,
+            path: path.clone(),
+        });
+        if expanded {
+            for child in children {
+                self.push_node(graph, child, depth + 1, path);
+            }
+        }
+        path.pop();
+    }
+
+    pub fn selected_row(&self) -> Option<&TreeRow> {
+        self.selection.selected().and_then(|i| self.rows.get(i))
+    }
+
+    pub fn toggle_reverse(&mut self, graph: &DependencyGraph) {
+        self.reverse = !self.reverse;
+        let root = self.root.take();
+        self.set_root(root.as_deref(), graph);
+    }
+
+    /// Expands the selected node, or collapses it if it is already expanded.
+    pub fn toggle_selected(&mut self, graph: &DependencyGraph) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+        if !row.has_children {
+            return;
+        }
+        let path = row.path.clone();
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+        self.rebuild(graph);
+    }
+
+    pub fn expand_selected(&mut self, graph: &DependencyGraph) {
+        if let Some(row) = self.selected_row()
+            && row.has_children
+            && !row.expanded
+        {
+            self.toggle_selected(graph);
+        }
+    }
+
+    /// Collapses the selected node, or moves to its parent if there is nothing to collapse.
+    pub fn collapse_selected(&mut self, graph: &DependencyGraph) {
+        let Some(selected) = self.selection.selected() else {
+            return;
+        };
+        let Some(row) = self.rows.get(selected) else {
+            return;
+        };
+        if row.expanded {
+            self.toggle_selected(graph);
+        } else if row.depth > 0 {
+            let parent_depth = row.depth - 1;
+            let parent = self.rows[..selected]
+                .iter()
+                .rposition(|r| r.depth == parent_depth);
+            self.selection.select(parent);
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.rows.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.selection.select(Some(i));
+    }
+
+    pub fn select_next(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) => {
+                if i >= self.rows.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.selection.select(Some(i));
+    }
+}
+
+impl Default for DependencyTreeState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyEventHandler for DependencyTreeState {
+    fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+        let graph = &app.state.dependency_graph;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Right | KeyCode::Char('l') => self.expand_selected(graph),
+            KeyCode::Left | KeyCode::Char('h') => self.collapse_selected(graph),
+            KeyCode::Char(' ') => self.toggle_selected(graph),
+            KeyCode::Char('r') => self.toggle_reverse(graph),
+            KeyCode::Enter => {
+                if let Some(name) = self.selected_row().map(|row| row.name.clone()) {
+                    app.input_mode = InputMode::Normal;
+                    app.jump_to_package(&name);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('t') => {
+                app.input_mode = InputMode::Normal;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    packages::{cache::CachedFile, desc::Desc, downgrade::InstallPreview, vercmp::vercmp},
+    tui::{
+        app::App,
+        app_states::{app_state::InputMode, state::KeyEventHandler},
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::ListState;
+use std::{cmp::Ordering, io};
+
+/// A cached package file with the metadata read from its `.PKGINFO`.
+pub struct CachedVersion {
+    pub file: CachedFile,
+    /// Why the metadata could not be read, if it could not
+    pub desc: Result<Desc, String>,
+}
+
+/// Manages the downgrade modal, which lists the cached versions of a package
+/// and previews installing one of them with `pacman -U`.
+#[derive(Default)]
+pub struct DowngradeState {
+    pub package: String,
+    pub installed_version: Option<String>,
+    /// Newest version first
+    pub versions: Vec<CachedVersion>,
+    pub selection: ListState,
+    /// Set once a version is picked
+    pub preview: Option<InstallPreview>,
+    pub scroll: u16,
+}
+
+impl DowngradeState {
+    /// Starts on the newest version older than the installed one, the usual
+    /// reason to open the list.
+    pub fn new(package: String, installed_version: Option<String>, versions: Vec<CachedVersion>) -> Self {
+        let selected = installed_version
+            .as_deref()
+            .and_then(|installed| {
+                versions
+                    .iter()
+                    .position(|v| vercmp(&v.file.version, installed) == Ordering::Less)
+            })
+            .unwrap_or(0);
+        Self {
+            package,
+            installed_version,
+            versions,
+            selection: ListState::default().with_selected(Some(selected)),
+            ..Self::default()
+        }
+    }
+
+    pub fn selected_version(&self) -> Option<&CachedVersion> {
+        self.selection.selected().and_then(|i| self.versions.get(i))
+    }
+
+    fn select_previous(&mut self) {
+        if self.versions.is_empty() {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) if i > 0 => i - 1,
+            _ => self.versions.len() - 1,
+        };
+        self.selection.select(Some(i));
+    }
+
+    fn select_next(&mut self) {
+        if self.versions.is_empty() {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) if i < self.versions.len() - 1 => i + 1,
+            _ => 0,
+        };
+        self.selection.select(Some(i));
+    }
+}
+
+impl KeyEventHandler for DowngradeState {
+    fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+        if self.preview.is_none() {
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+                KeyCode::Enter => match self.selected_version().map(|v| &v.desc) {
+                    Some(Ok(desc)) => {
+                        self.preview = Some(InstallPreview::compute(desc, &app.state.packages));
+                        self.scroll = 0;
+                    }
+                    Some(Err(e)) => app.output.error(format!("Cannot read the package file: {}", e)),
+                    None => {}
+                },
+                KeyCode::Esc | KeyCode::Char('q') => app.input_mode = InputMode::Normal,
+                _ => {}
+            }
+            return Ok(false);
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Enter | KeyCode::Char('y') => {
+                let Some(version) = self.selected_version() else {
+                    return Ok(false);
+                };
+                app.input_mode = InputMode::Normal;
+                match app.source.install_files_command(&[version.file.path.as_path()]) {
+                    Some(command) => {
+                        app.command_to_run = Some(command);
+                        return Ok(true);
+                    }
+                    None => app.output.warn(format!(
+                        "The package source is read-only, {} {} was not installed.",
+                        self.package, version.file.version
+                    )),
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('n') => self.preview = None,
+            _ => {}
+        }
+        Ok(false)
+    }
+}
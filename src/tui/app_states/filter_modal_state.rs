@@ -84,24 +84,24 @@ impl FilterModalState {
             FilterFocus::Search => return,
         };
 
-        if let Some(selected) = selected_index {
-            if let Some(key) = items.get(selected) {
-                let current_state = filters.get(key).cloned().unwrap_or_default();
-                let next_state = if forward {
-                    match current_state {
-                        FilterState::Ignore => FilterState::Include,
-                        FilterState::Include => FilterState::Exclude,
-                        FilterState::Exclude => FilterState::Ignore,
-                    }
-                } else {
-                    match current_state {
-                        FilterState::Ignore => FilterState::Exclude,
-                        FilterState::Exclude => FilterState::Include,
-                        FilterState::Include => FilterState::Ignore,
-                    }
-                };
-                filters.insert(key.clone(), next_state);
-            }
+        if let Some(selected) = selected_index
+            && let Some(key) = items.get(selected)
+        {
+            let current_state = filters.get(key).cloned().unwrap_or_default();
+            let next_state = if forward {
+                match current_state {
+                    FilterState::Ignore => FilterState::Include,
+                    FilterState::Include => FilterState::Exclude,
+                    FilterState::Exclude => FilterState::Ignore,
+                }
+            } else {
+                match current_state {
+                    FilterState::Ignore => FilterState::Exclude,
+                    FilterState::Exclude => FilterState::Include,
+                    FilterState::Include => FilterState::Ignore,
+                }
+            };
+            filters.insert(key.clone(), next_state);
         }
     }
 }
@@ -146,25 +146,16 @@ impl KeyEventHandler for FilterModalState {
                         self.cursor_position += 1;
                         self.update_filtered_options(&app.state.all_tags, &app.state.all_repos);
                     }
-                    KeyCode::Backspace => {
-                        if self.cursor_position > 0 {
-                            self.cursor_position -= 1;
-                            self.input.remove(self.cursor_position);
-                            self.update_filtered_options(
-                                &app.state.all_tags,
-                                &app.state.all_repos,
-                            );
-                        }
+                    KeyCode::Backspace if self.cursor_position > 0 => {
+                        self.cursor_position -= 1;
+                        self.input.remove(self.cursor_position);
+                        self.update_filtered_options(&app.state.all_tags, &app.state.all_repos);
                     }
-                    KeyCode::Left => {
-                        if self.cursor_position > 0 {
-                            self.cursor_position -= 1;
-                        }
+                    KeyCode::Left if self.cursor_position > 0 => {
+                        self.cursor_position -= 1;
                     }
-                    KeyCode::Right => {
-                        if self.cursor_position < self.input.len() {
-                            self.cursor_position += 1;
-                        }
+                    KeyCode::Right if self.cursor_position < self.input.len() => {
+                        self.cursor_position += 1;
                     }
                     KeyCode::Tab => {
                         self.focus = FilterFocus::Tags;
@@ -1,4 +1,8 @@
 pub mod app_state;
+pub mod cache_cleanup_state;
+pub mod config_drift_state;
+pub mod dependency_tree_state;
+pub mod downgrade_state;
 pub mod filter_modal_state;
 pub mod message_log;
 pub mod normal_state;
@@ -7,4 +11,8 @@ pub mod state;
 pub mod tag_modal_state;
 pub mod search_state;
 pub mod show_mode_state;
-pub mod action_modal_state;
\ No newline at end of file
+pub mod action_modal_state;pub mod transactions_state;
+pub mod time_travel_state;
+pub mod owner_search_state;
+pub mod removal_preview_state;
+pub mod unowned_state;
@@ -17,27 +17,23 @@ impl KeyEventHandler for NormalState {
 
         if key_char != '\0' {
             for action in app.config.actions.clone() {
-                if action.key.key == key_char && action.key.shift == shift {
-                    if let crate::config::ActionType::Command { .. } = action.action_type {
-                        return Ok(app.execute_config_action(&action));
-                    }
+                if action.key.key == key_char
+                    && action.key.shift == shift
+                    && let crate::config::ActionType::Command { .. } = action.action_type
+                {
+                    return Ok(app.execute_config_action(&action));
                 }
             }
         }
 
-        if key.modifiers == KeyModifiers::SHIFT {
-            match key.code {
-
-                _ => {}
-            }
-        }
-
         match key.code {
             KeyCode::Char('q') => return Ok(true),
             KeyCode::Char('k') => app.select_previous_package(), 
             KeyCode::Char('j') => app.select_next_package(),   
             KeyCode::Up => app.output.scroll_up(1),               
             KeyCode::Down => app.output.scroll_down(1),             
+            KeyCode::PageUp => app.info_scroll = app.info_scroll.saturating_sub(5),
+            KeyCode::PageDown => app.info_scroll = app.info_scroll.saturating_add(5),
             KeyCode::Char('s') => {
                 app.input_mode = InputMode::Sorting;
                 app.sort_state.selection.select(Some(0));
@@ -85,6 +81,36 @@ impl KeyEventHandler for NormalState {
                 }
             }
 
+            KeyCode::Char('D') => app.set_dependency_filter(false),
+            KeyCode::Char('R') => app.set_dependency_filter(true),
+            KeyCode::Esc => {
+                app.marked_packages.clear();
+                app.clear_dependency_filter();
+            }
+            KeyCode::Char(' ') => app.toggle_mark(),
+            KeyCode::Char('e') => app.request_install_reason(true),
+            KeyCode::Char('E') => app.request_install_reason(false),
+            KeyCode::Char('t') => {
+                if app.dependency_tree_state.rows.is_empty() {
+                    app.output.warn("No dependency tree to navigate.".to_string());
+                } else {
+                    app.input_mode = InputMode::DependencyTree;
+                }
+            }
+
+            KeyCode::Char('L') => app.open_transactions(),
+            KeyCode::Char('T') => app.open_time_travel(),
+            KeyCode::Char('O') => app.open_owner_search(),
+            KeyCode::Char('C') => app.open_cache_cleanup(),
+            KeyCode::Char('V') => app.open_downgrade(),
+            KeyCode::Char('M') => app.open_config_drift(),
+            KeyCode::Char('I') => app.start_integrity_check(),
+            KeyCode::Char('U') => app.open_unowned_files(),
+            KeyCode::Char('x') => app.open_removal_preview(),
+            KeyCode::Char('X') => app.open_tag_removal_preview(),
+            KeyCode::Tab => app.toggle_info_tab(),
+            KeyCode::Char('w') => app.toggle_why_installed(),
+
             KeyCode::Char('c') =>{
                 app.output.clear();
                 app.output.info("Output cleared.".to_string());
@@ -0,0 +1,93 @@
+use crate::{
+    packages::files::FileOwners,
+    tui::{
+        app::App,
+        app_states::{app_state::InputMode, state::KeyEventHandler},
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::ListState;
+use std::io;
+
+/// Manages the file owner search, the equivalent of `pacman -Qo`.
+#[derive(Default)]
+pub struct OwnerSearchState {
+    pub input: String,
+    /// (path, owning package) pairs matching the input
+    pub results: Vec<(String, String)>,
+    pub selection: ListState,
+}
+
+impl OwnerSearchState {
+    /// Searches the index for the input; `None` while it is still being built
+    pub fn update_results(&mut self, owners: Option<&FileOwners>) {
+        self.results = owners
+            .map(|owners| owners.search(&self.input))
+            .unwrap_or_default();
+        self.selection
+            .select(if self.results.is_empty() { None } else { Some(0) });
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.results.is_empty() {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.results.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.selection.select(Some(i));
+    }
+
+    pub fn select_next(&mut self) {
+        if self.results.is_empty() {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) => {
+                if i >= self.results.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.selection.select(Some(i));
+    }
+}
+
+impl KeyEventHandler for OwnerSearchState {
+    fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+        match key.code {
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                self.update_results(app.state.file_owners.as_deref());
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.update_results(app.state.file_owners.as_deref());
+            }
+            KeyCode::Up => self.select_previous(),
+            KeyCode::Down => self.select_next(),
+            KeyCode::Enter => {
+                if let Some((_, owner)) = self.selection.selected().and_then(|i| self.results.get(i)) {
+                    let owner = owner.clone();
+                    app.input_mode = InputMode::Normal;
+                    app.jump_to_package(&owner);
+                }
+            }
+            KeyCode::Esc => {
+                app.input_mode = InputMode::Normal;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{
+    packages::removal::RemovalImpact,
+    tui::{
+        app::App,
+        app_states::{app_state::InputMode, state::KeyEventHandler},
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::ListState;
+use std::io;
+
+/// Manages the removal preview modal, which shows what a removal would take
+/// with it. When opened for a whole tag, a tag has to be picked first.
+#[derive(Default)]
+pub struct RemovalPreviewState {
+    pub title: String,
+    /// `None` while a tag is being picked
+    pub impact: Option<RemovalImpact>,
+    /// Command run when the removal is confirmed; without one the preview
+    /// is informational only
+    pub command: Option<Vec<String>>,
+    pub scroll: u16,
+    pub tag_selection: ListState,
+}
+
+impl RemovalPreviewState {
+    pub fn preview(title: String, impact: RemovalImpact, command: Option<Vec<String>>) -> Self {
+        Self {
+            title,
+            impact: Some(impact),
+            command,
+            ..Self::default()
+        }
+    }
+
+    pub fn pick_tag() -> Self {
+        Self {
+            title: "Removal Impact of Tag".to_string(),
+            tag_selection: ListState::default().with_selected(Some(0)),
+            ..Self::default()
+        }
+    }
+
+    fn select_previous_tag(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let i = match self.tag_selection.selected() {
+            Some(i) if i > 0 => i - 1,
+            _ => count - 1,
+        };
+        self.tag_selection.select(Some(i));
+    }
+
+    fn select_next_tag(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let i = match self.tag_selection.selected() {
+            Some(i) if i < count - 1 => i + 1,
+            _ => 0,
+        };
+        self.tag_selection.select(Some(i));
+    }
+}
+
+impl KeyEventHandler for RemovalPreviewState {
+    fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+        if self.impact.is_none() {
+            let count = app.state.all_tags.len();
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.select_previous_tag(count),
+                KeyCode::Down | KeyCode::Char('j') => self.select_next_tag(count),
+                KeyCode::Enter => {
+                    if let Some(tag) = self
+                        .tag_selection
+                        .selected()
+                        .and_then(|i| app.state.all_tags.get(i))
+                    {
+                        let impact = RemovalImpact::for_tag(
+                            tag,
+                            &app.state.packages,
+                            &app.state.dependency_graph,
+                        );
+                        self.title = format!("Removal Impact of Tag '{}'", tag);
+                        self.impact = Some(impact);
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('q') => app.input_mode = InputMode::Normal,
+                _ => {}
+            }
+            return Ok(false);
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Enter | KeyCode::Char('y') if self.command.is_some() => {
+                app.command_to_run = self.command.take();
+                app.input_mode = InputMode::Normal;
+                return Ok(true);
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('n') => {
+                app.input_mode = InputMode::Normal;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
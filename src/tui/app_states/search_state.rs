@@ -47,22 +47,16 @@ impl KeyEventHandler for SearchState {
                 app.search_cursor_position += 1;
                 app.apply_filters();
             }
-            KeyCode::Backspace => {
-                if app.search_cursor_position > 0 {
-                    app.search_cursor_position -= 1;
-                    app.search_input.remove(app.search_cursor_position);
-                    app.apply_filters();
-                }
+            KeyCode::Backspace if app.search_cursor_position > 0 => {
+                app.search_cursor_position -= 1;
+                app.search_input.remove(app.search_cursor_position);
+                app.apply_filters();
             }
-            KeyCode::Left => {
-                if app.search_cursor_position > 0 {
-                    app.search_cursor_position -= 1;
-                }
+            KeyCode::Left if app.search_cursor_position > 0 => {
+                app.search_cursor_position -= 1;
             }
-            KeyCode::Right => {
-                if app.search_cursor_position < app.search_input.len() {
-                    app.search_cursor_position += 1;
-                }
+            KeyCode::Right if app.search_cursor_position < app.search_input.len() => {
+                app.search_cursor_position += 1;
             }
             KeyCode::Enter => {
                 app.input_mode = InputMode::Normal;
@@ -24,7 +24,17 @@ impl ShowModeState {
                 ShowMode::ExplicitlyInstalled,
                 ShowMode::Dependencies,
                 ShowMode::Orphans,
+                ShowMode::Leaves,
+                ShowMode::RedundantExplicit,
                 ShowMode::AllAvailable, 
+                ShowMode::AurNeedsAttention,
+                ShowMode::AurUpdates,
+                ShowMode::RepoUpgrades,
+                ShowMode::TimeTravel,
+                ShowMode::PackageCache,
+                ShowMode::ConfigDrift,
+                ShowMode::ModifiedConfigs,
+                ShowMode::IntegrityProblems,
             ],
             selection: ListState::default(),
             active_show_mode: ShowMode::AllInstalled,
@@ -81,10 +91,28 @@ impl KeyEventHandler for ShowModeState {
             KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
             KeyCode::Down | KeyCode::Char('j') => self.select_next(),
             KeyCode::Enter => {
-                if let Some(selected) = self.selection.selected() {
-                    if let Some(show_mode) = self.options.get(selected) {
-                        self.active_show_mode = *show_mode;
+                if let Some(selected) = self.selection.selected()
+                    && let Some(show_mode) = self.options.get(selected)
+                {
+                    if *show_mode == ShowMode::TimeTravel && app.state.time_travel.is_none() {
+                        // Nothing to show yet, ask where to travel first
+                        app.open_time_travel();
+                        return Ok(false);
                     }
+                    if *show_mode == ShowMode::PackageCache {
+                        app.scan_package_cache();
+                    }
+                    if *show_mode == ShowMode::ConfigDrift {
+                        app.scan_config_drift();
+                    }
+                    // Nothing to show before the first check
+                    if *show_mode == ShowMode::IntegrityProblems
+                        && app.integrity_check.is_none()
+                        && app.state.packages.iter().all(|p| p.integrity.is_none())
+                    {
+                        app.start_integrity_check();
+                    }
+                    self.active_show_mode = *show_mode;
                 }
                 app.input_mode = InputMode::Normal;
             }
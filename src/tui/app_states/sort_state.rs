@@ -25,6 +25,8 @@ impl SortState {
                 SortKey::InstallDate,
                 SortKey::UpdateDate,
                 SortKey::Popularity,
+                SortKey::Votes,
+                SortKey::LastModified,
             ],
             selection: ListState::default(),
             active_sort_key: SortKey::Name,
@@ -72,10 +74,10 @@ impl KeyEventHandler for SortState {
             KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
             KeyCode::Down | KeyCode::Char('j') => self.select_next(),
             KeyCode::Enter => {
-                if let Some(selected) = self.selection.selected() {
-                    if let Some(sort_key) = self.options.get(selected) {
-                        self.active_sort_key = *sort_key;
-                    }
+                if let Some(selected) = self.selection.selected()
+                    && let Some(sort_key) = self.options.get(selected)
+                {
+                    self.active_sort_key = *sort_key;
                 }
                 app.input_mode = InputMode::Normal;
             }
@@ -124,41 +124,40 @@ impl KeyEventHandler for TagModalState {
     fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
         match key.code {
             KeyCode::Enter => {
-                if let Some(selected_index) = app.selected_package.selected() {
-                    if let Some(selected_pkg_name) =
+                if let Some(selected_index) = app.selected_package.selected()
+                    && let Some(selected_pkg_name) =
                         app.state.filtered_packages.get(selected_index).map(|p| p.name.clone())
-                    {
-                        let tag_to_apply = self.input.trim().to_string();
-                        if !tag_to_apply.is_empty() {
-                            let result = if matches!(app.input_mode, InputMode::Tagging) {
-                                db::add_tag(&selected_pkg_name, &tag_to_apply)
-                            } else {
-                                db::remove_tag(&selected_pkg_name, &tag_to_apply)
-                            };
-                            match result {
-                                Ok(msg) => {
-                                    app.output.info(msg);
-                                    // Find the package in the main list and update its tags
-                                    if let Some(pkg_to_update) = app
-                                        .state
-                                        .packages
-                                        .iter_mut()
-                                        .find(|p| p.name == selected_pkg_name)
-                                    {
-                                        if matches!(app.input_mode, InputMode::Tagging) {
-                                            if !pkg_to_update.tags.contains(&tag_to_apply) {
-                                                pkg_to_update.tags.push(tag_to_apply);
-                                                pkg_to_update.tags.sort();
-                                            }
-                                        } else {
-                                            pkg_to_update.tags.retain(|t| t != &tag_to_apply);
+                {
+                    let tag_to_apply = self.input.trim().to_string();
+                    if !tag_to_apply.is_empty() {
+                        let result = if matches!(app.input_mode, InputMode::Tagging) {
+                            db::add_tag(&selected_pkg_name, &tag_to_apply)
+                        } else {
+                            db::remove_tag(&selected_pkg_name, &tag_to_apply)
+                        };
+                        match result {
+                            Ok(msg) => {
+                                app.output.info(msg);
+                                // Find the package in the main list and update its tags
+                                if let Some(pkg_to_update) = app
+                                    .state
+                                    .packages
+                                    .iter_mut()
+                                    .find(|p| p.name == selected_pkg_name)
+                                {
+                                    if matches!(app.input_mode, InputMode::Tagging) {
+                                        if !pkg_to_update.tags.contains(&tag_to_apply) {
+                                            pkg_to_update.tags.push(tag_to_apply);
+                                            pkg_to_update.tags.sort();
                                         }
+                                    } else {
+                                        pkg_to_update.tags.retain(|t| t != &tag_to_apply);
                                     }
-                                    app.reload_tags();
-                                }
-                                Err(e) => {
-                                    app.output.error(format!("Error: {}", e));
                                 }
+                                app.reload_tags();
+                            }
+                            Err(e) => {
+                                app.output.error(format!("Error: {}", e));
                             }
                         }
                     }
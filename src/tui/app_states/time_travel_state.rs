@@ -0,0 +1,54 @@
+use crate::tui::{
+    app::App,
+    app_states::{app_state::InputMode, state::KeyEventHandler},
+};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+use std::io;
+
+/// Manages the date prompt of the time travel mode.
+#[derive(Default)]
+pub struct TimeTravelState {
+    pub input: String,
+}
+
+/// Parses `YYYY-MM-DD HH:MM` or `YYYY-MM-DD` in local time.
+/// A bare date means the end of that day.
+fn parse_moment(input: &str) -> Option<DateTime<Utc>> {
+    let input = input.trim();
+    let naive = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(input, "%Y-%m-%d").ok()?;
+            Some(date.and_time(NaiveTime::from_hms_opt(23, 59, 59)?))
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .latest()
+        .map(|moment| moment.with_timezone(&Utc))
+}
+
+impl KeyEventHandler for TimeTravelState {
+    fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+        match key.code {
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter => match parse_moment(&self.input) {
+                Some(moment) => {
+                    app.input_mode = InputMode::Normal;
+                    app.time_travel_to(moment);
+                }
+                None => app
+                    .output
+                    .error(format!("Invalid date '{}', expected YYYY-MM-DD [HH:MM].", self.input)),
+            },
+            KeyCode::Esc => {
+                app.input_mode = InputMode::Normal;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
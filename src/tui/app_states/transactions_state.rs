@@ -0,0 +1,88 @@
+use crate::tui::{
+    app::App,
+    app_states::{app_state::InputMode, state::KeyEventHandler},
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::ListState;
+use std::io;
+
+/// Manages the recent transactions modal, listing pacman.log transactions
+/// newest first.
+pub struct TransactionsState {
+    pub selection: ListState,
+}
+
+impl TransactionsState {
+    pub fn new() -> Self {
+        Self {
+            selection: ListState::default(),
+        }
+    }
+
+    /// Index of the selected transaction in `PacmanLog::transactions`
+    pub fn selected_transaction(&self, count: usize) -> Option<usize> {
+        let selected = self.selection.selected()?;
+        count.checked_sub(selected + 1)
+    }
+
+    pub fn select_previous(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) => {
+                if i == 0 {
+                    count - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.selection.select(Some(i));
+    }
+
+    pub fn select_next(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) => {
+                if i >= count - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.selection.select(Some(i));
+    }
+}
+
+impl Default for TransactionsState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyEventHandler for TransactionsState {
+    fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+        let count = app.state.pacman_log.transactions.len();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(count),
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(count),
+            KeyCode::Enter => {
+                if let Some(transaction) = self.selected_transaction(count) {
+                    app.input_mode = InputMode::Normal;
+                    app.time_travel_to_transaction(transaction);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('L') => {
+                app.input_mode = InputMode::Normal;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
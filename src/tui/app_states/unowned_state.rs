@@ -0,0 +1,77 @@
+use crate::{
+    packages::unowned::UnownedFiles,
+    tui::{
+        app::App,
+        app_states::{app_state::InputMode, state::KeyEventHandler},
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::ListState;
+use std::io;
+
+/// Manages the modal listing the files no installed package owns, one row
+/// per directory followed by its unowned entries.
+#[derive(Default)]
+pub struct UnownedState {
+    pub files: UnownedFiles,
+    pub selection: ListState,
+    /// Whether the search is still running, see `App::open_unowned_files`
+    pub scanning: bool,
+}
+
+impl UnownedState {
+    pub fn new(files: UnownedFiles) -> Self {
+        Self {
+            files,
+            selection: ListState::default().with_selected(Some(0)),
+            scanning: false,
+        }
+    }
+
+    pub fn scanning() -> Self {
+        Self {
+            scanning: true,
+            ..Self::default()
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        self.files.dirs().len() + self.files.path_count()
+    }
+
+    fn select_previous(&mut self) {
+        let rows = self.row_count();
+        if rows == 0 {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) if i > 0 => i - 1,
+            _ => rows - 1,
+        };
+        self.selection.select(Some(i));
+    }
+
+    fn select_next(&mut self) {
+        let rows = self.row_count();
+        if rows == 0 {
+            return;
+        }
+        let i = match self.selection.selected() {
+            Some(i) if i < rows - 1 => i + 1,
+            _ => 0,
+        };
+        self.selection.select(Some(i));
+    }
+}
+
+impl KeyEventHandler for UnownedState {
+    fn handle_key_event(&mut self, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Esc | KeyCode::Char('q') => app.input_mode = InputMode::Normal,
+            _ => {}
+        }
+        Ok(false)
+    }
+}
@@ -51,8 +51,7 @@ pub fn handle_events(app: &mut App) -> std::io::Result<bool> {
                     }
                     InputMode::Searching => {
                         let mut handler = app.search_state;
-                        let result = handler.handle_key_event(app, key)?; 
-                        result
+                        handler.handle_key_event(app, key)?
                     }
                     InputMode::Showing => {
                         let mut handler = std::mem::take(&mut app.show_mode_state);
@@ -65,6 +64,60 @@ pub fn handle_events(app: &mut App) -> std::io::Result<bool> {
 
                         result
                     }
+                    InputMode::DependencyTree => {
+                        let mut handler = std::mem::take(&mut app.dependency_tree_state);
+                        let result = handler.handle_key_event(app, key)?;
+                        app.dependency_tree_state = handler;
+                        result
+                    }
+                    InputMode::Transactions => {
+                        let mut handler = std::mem::take(&mut app.transactions_state);
+                        let result = handler.handle_key_event(app, key)?;
+                        app.transactions_state = handler;
+                        result
+                    }
+                    InputMode::TimeTravel => {
+                        let mut handler = std::mem::take(&mut app.time_travel_state);
+                        let result = handler.handle_key_event(app, key)?;
+                        app.time_travel_state = handler;
+                        result
+                    }
+                    InputMode::OwnerSearch => {
+                        let mut handler = std::mem::take(&mut app.owner_search_state);
+                        let result = handler.handle_key_event(app, key)?;
+                        app.owner_search_state = handler;
+                        result
+                    }
+                    InputMode::CacheCleanup => {
+                        let mut handler = std::mem::take(&mut app.cache_cleanup_state);
+                        let result = handler.handle_key_event(app, key)?;
+                        app.cache_cleanup_state = handler;
+                        result
+                    }
+                    InputMode::ConfigDrift => {
+                        let mut handler = std::mem::take(&mut app.config_drift_state);
+                        let result = handler.handle_key_event(app, key)?;
+                        app.config_drift_state = handler;
+                        result
+                    }
+                    InputMode::Downgrade => {
+                        let mut handler = std::mem::take(&mut app.downgrade_state);
+                        let result = handler.handle_key_event(app, key)?;
+                        app.downgrade_state = handler;
+                        result
+                    }
+                    InputMode::Unowned => {
+                        let mut handler = std::mem::take(&mut app.unowned_state);
+                        let result = handler.handle_key_event(app, key)?;
+                        app.unowned_state = handler;
+                        result
+                    }
+                    InputMode::RemovalPreview => {
+                        let mut handler = std::mem::take(&mut app.removal_preview_state);
+                        let result = handler.handle_key_event(app, key)?;
+                        app.removal_preview_state = handler;
+                        result
+                    }
                     InputMode::Action => {
                         let mut handler = std::mem::take(&mut app.action_state);
                         let result = handler.handle_key_event(app, key)?;
@@ -5,39 +5,67 @@ mod terminal;
 mod ui;
 
 use crate::error::AppError;
+use crate::packages::source::PackageSource;
 use crate::tui::app_states::
     app_state::LoadedData
 ;
 use app::App;
 use terminal::{init_terminal, restore_terminal};
 
+use std::sync::Arc;
 use tokio::sync::mpsc;
 
-pub async fn run_tui() -> Result<(), AppError> {
+pub async fn run_tui(source: Arc<dyn PackageSource>) -> Result<(), AppError> {
 
     let mut app;
 
     loop {
         let (tx, rx) = mpsc::channel(1);
-        app = App::new(rx);
+        app = App::new(rx, Arc::clone(&source));
 
         let mut terminal = init_terminal()?;
 
+        let source = Arc::clone(&source);
         tokio::spawn(async move {
-            let packages = crate::packages::pacman::get_all_packages()
-                .await
-                .unwrap_or_default();
+            let mut warnings = Vec::new();
+            let sync_packages = source.sync_packages(&mut warnings).unwrap_or_else(|e| {
+                warnings.push(format!("Failed to read sync databases: {}", e));
+                Vec::new()
+            });
+            let tags = crate::db::load_tags().unwrap_or_else(|e| {
+                warnings.push(format!("Failed to read tags: {}", e));
+                Default::default()
+            });
+            let packages = crate::packages::pacman::get_all_packages(
+                source.as_ref(),
+                &sync_packages,
+                &tags,
+                &mut warnings,
+            )
+            .await
+            .unwrap_or_else(|e| {
+                warnings.push(format!("Failed to read installed packages: {}", e));
+                Vec::new()
+            });
             let available_packages =
-                crate::packages::pacman::get_all_available_packages().unwrap_or_default();
-            let all_repos = crate::backend::get_all_repos(&available_packages);
-            let orphan_package_names =
-                crate::packages::pacman::get_orphan_package_names().unwrap_or_default();
+                crate::packages::pacman::get_all_available_packages(&sync_packages, &packages);
+            let all_repos =
+                crate::backend::get_all_repos(packages.iter().chain(&available_packages));
+            let dependency_graph = crate::packages::deps::DependencyGraph::build(&packages);
+            let orphans = crate::packages::orphans::Orphans::find(&packages, &dependency_graph);
+            let pacman_log = source.pacman_log().unwrap_or_else(|e| {
+                warnings.push(format!("Failed to read the pacman log: {}", e));
+                crate::packages::pacman_log::PacmanLog::default()
+            });
 
             let loaded_data = LoadedData {
                 packages,
                 available_packages,
                 all_repos,
-                orphan_package_names,
+                orphans,
+                dependency_graph,
+                pacman_log,
+                warnings,
             };
             // Send data to the main loop
             let _ = tx.send(loaded_data).await;
@@ -58,7 +86,7 @@ pub async fn run_tui() -> Result<(), AppError> {
                 command.args(&command_parts[1..]);
             }
 
-            let status = command.status().await.map_err(|e| AppError::Io(e))?;
+            let status = command.status().await.map_err(AppError::Io)?;
 
             if !status.success() {
                 eprintln!("\nCommand failed: {:?}", command_parts);
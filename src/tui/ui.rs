@@ -1,6 +1,20 @@
 use crate::tui::app::{App};
-use crate::backend::FilterState;
-use crate::tui::app_states::app_state::{ActionModalFocus, FilterFocus, InputMode, TagModalFocus};
+use crate::backend::{DependencyFilter, FilterState};
+use crate::packages::deps::{ChainLink, Dependency, WhyInstalled};
+use crate::packages::{
+    aur::AurPackage,
+    config_drift::DriftKind,
+    downgrade::InstallPreview,
+    models::{Package, ShowMode},
+    pacman::timestamp_to_date,
+    removal::RemovalImpact,
+    time_travel::Change,
+};
+use chrono::{DateTime, Local, Utc};
+use std::{cmp::Ordering, collections::HashSet};
+use crate::tui::app_states::app_state::{
+    ActionModalFocus, FilterFocus, InfoTab, InputMode, TagModalFocus,
+};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Margin, Rect, Position},
     style::{Color, Modifier, Style},
@@ -60,7 +74,16 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
     // Store the output log's area for mouse event handling
     app.output_log_area = right_layout[2];
 
-    render_package_info(frame, right_layout[0], app);
+    let info_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+        .split(right_layout[0]);
+
+    match app.info_tab {
+        InfoTab::Info => render_package_info(frame, info_layout[0], app),
+        InfoTab::Files => render_package_files(frame, info_layout[0], app),
+    }
+    render_dependency_tree(frame, info_layout[1], app);
     render_actions(frame, right_layout[1], app);
     render_output_window(frame, right_layout[2], app); // MODIFIED
 
@@ -72,15 +95,109 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         InputMode::Filtering => render_filter_modal(frame, app),
         InputMode::Showing => render_show_mode_modal(frame, app),
         InputMode::Action => render_action_modal(frame, app),
+        InputMode::Transactions => render_transactions_modal(frame, app),
+        InputMode::TimeTravel => render_time_travel_modal(frame, app),
+        InputMode::OwnerSearch => render_owner_search_modal(frame, app),
+        InputMode::RemovalPreview => render_removal_preview_modal(frame, app),
+        InputMode::CacheCleanup => render_cache_cleanup_modal(frame, app),
+        InputMode::Downgrade => render_downgrade_modal(frame, app),
+        InputMode::ConfigDrift => render_config_drift_modal(frame, app),
+        InputMode::Unowned => render_unowned_modal(frame, app),
         _ => {}
     }
 }
 
+// Version column of the package list: changes since the time travel
+// moment in that mode, why a package is orphaned in the orphans mode,
+// the cached versions in the package cache mode, the drifted config files
+// in the config drift mode, the changed backup files in the modified
+// configs mode, the failed paths in the integrity problems mode, available
+// updates otherwise
+fn version_column(app: &App, package: &Package) -> Option<String> {
+    if app.show_mode_state.active_show_mode == ShowMode::IntegrityProblems {
+        let Some(Ok(report)) = &package.integrity else {
+            return None;
+        };
+        return Some(format!("{} problem(s)", report.problems.len()));
+    }
+    if app.show_mode_state.active_show_mode == ShowMode::ModifiedConfigs {
+        let count = app.state.modified_configs.for_package(&package.name).len();
+        return Some(format!("{} changed config(s)", count));
+    }
+    if app.show_mode_state.active_show_mode == ShowMode::ConfigDrift {
+        let drift = app.state.config_drift.as_ref()?;
+        let kinds: Vec<String> = drift
+            .for_entry(&package.name)
+            .map(|file| format!(".{}", file.kind))
+            .collect();
+        return Some(kinds.join(", "));
+    }
+    if app.show_mode_state.active_show_mode == ShowMode::PackageCache {
+        let count = app.state.package_cache.as_ref()?.versions(&package.name).len();
+        return Some(format!("{} version(s)", count));
+    }
+    if app.show_mode_state.active_show_mode == ShowMode::Orphans {
+        return app.state.orphans.reason(&package.name).map(|reason| reason.to_string());
+    }
+    if app.show_mode_state.active_show_mode == ShowMode::TimeTravel {
+        let change = app.state.time_travel.as_ref()?.changes.get(&package.name)?;
+        return match change {
+            Change::Unchanged => None,
+            Change::InstalledSince => Some("installed since".to_string()),
+            Change::RemovedSince => Some(format!("{} -> removed", package.version)),
+            Change::UpdatedSince { now } => Some(format!("{} -> {}", package.version, now)),
+        };
+    }
+    package
+        .available_update()
+        .map(|new_version| format!("{} -> {}", package.version, new_version))
+}
+
 fn render_package_list(frame: &mut Frame, area: Rect, app: &mut App) {
+    let columns: Vec<Option<String>> = app
+        .state
+        .filtered_packages
+        .iter()
+        .map(|p| version_column(app, p))
+        .collect();
+    // Pad names so version changes line up as a column
+    let name_width = app
+        .state
+        .filtered_packages
+        .iter()
+        .zip(&columns)
+        .filter(|(_, column)| column.is_some())
+        .map(|(p, _)| p.name.len())
+        .max()
+        .unwrap_or_default();
+    let marking = !app.marked_packages.is_empty();
+    let mark_width = if marking { 2 } else { 0 };
     let items: Vec<ListItem> = app
         .state.filtered_packages
         .iter()
-        .map(|p| ListItem::new(p.name.clone()))
+        .zip(columns)
+        .map(|(p, column)| {
+            let marked = app.marked_packages.contains(&p.name);
+            // Reserve room for the mark on every row while anything is marked
+            let name = match (marking, marked) {
+                (false, _) => p.name.clone(),
+                (true, true) => format!("* {}", p.name),
+                (true, false) => format!("  {}", p.name),
+            };
+            let mut spans = match column {
+                Some(column) => vec![
+                    Span::raw(format!("{:<width$}  ", name, width = name_width + mark_width)),
+                    Span::styled(column, Style::default().fg(Color::Yellow)),
+                ],
+                None => vec![Span::raw(name)],
+            };
+            // Badge for packages carrying hand-edited configuration
+            if app.state.modified_configs.is_modified(&p.name) {
+                spans.push(Span::styled(" ✎", Style::default().fg(Color::Magenta)));
+            }
+            let item = ListItem::new(Line::from(spans));
+            if marked { item.style(Style::default().fg(Color::Cyan)) } else { item }
+        })
         .collect();
 
     let title = if app.is_loading {
@@ -92,7 +209,29 @@ fn render_package_list(frame: &mut Frame, area: Rect, app: &mut App) {
         } else {
             0
         };
-        format!("Packages ({}/{})", current, total) 
+        if app.show_mode_state.active_show_mode == ShowMode::PackageCache {
+            let cached: f64 = app.state.filtered_packages.iter().map(|p| p.size).sum();
+            format!("Packages ({}/{}, {:.2} MiB cached)", current, total, cached)
+        } else if app.show_mode_state.active_show_mode == ShowMode::ConfigDrift {
+            let files = app.state.config_drift.as_ref().map_or(0, |d| d.files().len());
+            format!("Packages ({}/{}, {} .pacnew/.pacsave)", current, total, files)
+        } else if app.show_mode_state.active_show_mode == ShowMode::RepoUpgrades {
+            let download_size: f64 = app
+                .state
+                .filtered_packages
+                .iter()
+                .filter_map(|p| p.download_size)
+                .sum();
+            format!("Packages ({}/{}, {:.2} MiB to download)", current, total, download_size)
+        } else if marking {
+            format!("Packages ({}/{}, {} marked)", current, total, app.marked_packages.len())
+        } else {
+            format!("Packages ({}/{})", current, total)
+        }
+    };
+    let title = match &app.integrity_check {
+        Some(check) => format!("{} Checking integrity {}/{}", title, check.checked, check.total),
+        None => title,
     };
 
     let list = List::new(items)
@@ -105,20 +244,74 @@ fn render_package_list(frame: &mut Frame, area: Rect, app: &mut App) {
 
 fn render_package_info(frame: &mut Frame, area: Rect, app: &App) {
     let block = Block::default()
-        .title("Package Information")
+        .title("Package Information | Files (Tab, PgUp/PgDn)")
         .borders(Borders::ALL);
 
     let info_text = if let Some(selected) = app.selected_package.selected() {
         if let Some(package) = app.state.filtered_packages.get(selected) {
+            let installed = match package.install_date {
+                Some(date) => date.format("%Y-%m-%d").to_string(),
+                // The local database entry has no %INSTALLDATE%
+                None if package.is_installed => "Unknown".to_string(),
+                None => "Not installed".to_string(),
+            };
+            let download_size = match package.download_size {
+                Some(size) => format!("\nDownload Size: {:.2} MiB", size),
+                None => String::new(),
+            };
+            let aur = match &package.aur {
+                Some(aur) => format_aur_info(aur, package.aur_stale_since),
+                None => String::new(),
+            };
+            let history = format_history(app, &package.name);
+            let why_installed = if app.show_why_installed && package.is_installed {
+                format_why_installed(app, &package.name)
+            } else {
+                String::new()
+            };
+            let cached = format_cached_versions(app, package);
+            let modified_configs = format_modified_configs(app, &package.name);
+            let integrity = format_integrity(package);
+            let orphaned = match app.state.orphans.reason(&package.name) {
+                Some(reason) => format!("\nOrphaned: {}", reason),
+                None => String::new(),
+            };
+            let graph = &app.state.dependency_graph;
+            let installed_optional = graph.optional_deps(&package.name);
+            let optional_deps: Vec<String> = package
+                .optional_deps
+                .iter()
+                .map(|raw| {
+                    let dep = Dependency::parse(raw);
+                    if installed_optional.contains(&dep.name) {
+                        format!("{} [installed]", raw)
+                    } else {
+                        raw.clone()
+                    }
+                })
+                .collect();
             format!(
-                "Name: {}\nVersion: {}\nRepository: {:?}\nDescription: {}\nInstalled: {}\nSize: {:.2} MiB\nTags: {}",
+                "{}Name: {}\nVersion: {}\nRepository: {}\nDescription: {}\nBuilt: {}\nInstalled: {}\nSize: {:.2} MiB{}{}{}\nDepends On: {}\nOptional Deps: {}\nRequired By: {}\nOptional For: {}{}{}{}\nTags: {}{}",
+                why_installed,
                 package.name,
                 package.version,
                 package.repository,
                 package.description,
-                package.install_date.format("%Y-%m-%d"),
+                package.build_date.format("%Y-%m-%d"),
+                installed,
                 package.size,
-                package.tags.join(", ")
+                download_size,
+                cached,
+                aur,
+                join_or_none(&package.depends),
+                join_or_none(&optional_deps),
+                join_or_none(graph.required_by(&package.name)),
+                join_or_none(graph.optional_for(&package.name)),
+                orphaned,
+                modified_configs,
+                integrity,
+                package.tags.join(", "),
+                history
             )
         } else {
             "No package selected".to_string()
@@ -127,13 +320,346 @@ fn render_package_info(frame: &mut Frame, area: Rect, app: &App) {
         "No packages found".to_string()
     };
 
-    let paragraph = Paragraph::new(info_text).block(block);
+    // In the config drift mode the diffs come first, as they are what the
+    // mode is about
+    let mut text = Text::from(info_text);
+    if let Some(package) = app
+        .selected_package
+        .selected()
+        .and_then(|i| app.state.filtered_packages.get(i))
+    {
+        let mut lines = format_config_drift(app, &package.name);
+        lines.append(&mut text.lines);
+        text.lines = lines;
+    }
+
+    // Keep at least the last line visible when scrolled past the end
+    let max_scroll = text.lines.len().saturating_sub(1) as u16;
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .scroll((app.info_scroll.min(max_scroll), 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn render_package_files(frame: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .title("Package Information | Files (Tab, PgUp/PgDn)")
+        .borders(Borders::ALL);
+
+    let lines: Vec<Line> = match &app.package_files {
+        None => vec![Line::from("No package selected")],
+        Some((_, Err(e))) => vec![Line::from(format!("Cannot list files: {}", e))],
+        Some((_, Ok(files))) => {
+            let missing = files.iter().filter(|f| !f.exists()).count();
+            let total: u64 = files
+                .iter()
+                .filter(|f| !f.is_dir())
+                .filter_map(|f| f.size)
+                .sum();
+            let mut lines = vec![Line::from(format!(
+                "{} paths, {} missing, {} on disk",
+                files.len(),
+                missing,
+                format_size(total)
+            ))];
+            lines.extend(files.iter().map(|file| {
+                let size = match file.size {
+                    None => "missing".to_string(),
+                    Some(_) if file.is_dir() => String::new(),
+                    Some(size) => format_size(size),
+                };
+                let style = if file.exists() {
+                    Style::default()
+                } else {
+                    Style::default().fg(Color::Red)
+                };
+                Line::styled(format!("{:>10}  /{}", size, file.path), style)
+            }));
+            lines
+        }
+    };
+
+    let max_scroll = lines.len().saturating_sub(1) as u16;
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((app.info_scroll.min(max_scroll), 0));
     frame.render_widget(paragraph, area);
 }
 
+fn format_size(bytes: u64) -> String {
+    const KIB: f64 = 1024.0;
+    let bytes_f = bytes as f64;
+    if bytes_f >= KIB * KIB * KIB {
+        format!("{:.2} GiB", bytes_f / (KIB * KIB * KIB))
+    } else if bytes_f >= KIB * KIB {
+        format!("{:.2} MiB", bytes_f / (KIB * KIB))
+    } else if bytes_f >= KIB {
+        format!("{:.1} KiB", bytes_f / KIB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+// Shortest chains from explicitly installed packages to the package,
+// shown above the package information
+fn format_why_installed(app: &App, package_name: &str) -> String {
+    let explicit: HashSet<&str> = app
+        .state
+        .packages
+        .iter()
+        .filter(|p| p.is_explicit)
+        .map(|p| p.name.as_str())
+        .collect();
+    let (heading, chains) = match app.state.dependency_graph.why_installed(package_name, &explicit) {
+        WhyInstalled::Explicit => return "Why Installed: explicitly installed\n\n".to_string(),
+        WhyInstalled::Unneeded => {
+            return "Why Installed: nothing explicitly installed needs it\n\n".to_string();
+        }
+        WhyInstalled::Required(chains) => ("Why Installed:", chains),
+        WhyInstalled::OptionalOnly(chains) => ("Why Installed (only as an optional dependency):", chains),
+    };
+    let mut text = heading.to_string();
+    for chain in chains {
+        text.push_str("\n  ");
+        text.push_str(&format_chain(&chain));
+    }
+    text.push_str("\n\n");
+    text
+}
+
+// Optional links are drawn with a dashed arrow
+fn format_chain(chain: &[ChainLink]) -> String {
+    let mut text = String::new();
+    for (i, link) in chain.iter().enumerate() {
+        if i > 0 {
+            text.push_str(if link.optional { " ⇢ " } else { " → " });
+        }
+        text.push_str(&link.name);
+    }
+    text
+}
+
+// Cached files of the package in the package cache mode
+fn format_cached_versions(app: &App, package: &Package) -> String {
+    let Some(cache) = &app.state.package_cache else {
+        return String::new();
+    };
+    if app.show_mode_state.active_show_mode != ShowMode::PackageCache {
+        return String::new();
+    }
+    let mut text = "\nCached Versions:".to_string();
+    for file in cache.versions(&package.name) {
+        let installed = if package.is_installed && file.version == package.version {
+            " [installed]"
+        } else {
+            ""
+        };
+        text.push_str(&format!(
+            "\n  {} ({}){}",
+            file.version,
+            format_size(file.size),
+            installed
+        ));
+    }
+    text
+}
+
+// Diffs of the package's .pacnew and .pacsave files in the config drift mode
+fn format_config_drift(app: &App, name: &str) -> Vec<Line<'static>> {
+    let Some(drift) = &app.state.config_drift else {
+        return Vec::new();
+    };
+    if app.show_mode_state.active_show_mode != ShowMode::ConfigDrift {
+        return Vec::new();
+    }
+    let mut lines = Vec::new();
+    for file in drift.for_entry(name) {
+        lines.push(Line::styled(
+            format!("/{} has a .{}", file.path, file.kind),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        match &file.diff {
+            Ok(diff) if diff.is_empty() => lines.push(Line::from("  Both versions are identical")),
+            Ok(diff) => lines.extend(diff.lines().map(|line| {
+                let color = if line.starts_with("+++") || line.starts_with("---") {
+                    Color::White
+                } else if line.starts_with('+') {
+                    Color::Green
+                } else if line.starts_with('-') {
+                    Color::Red
+                } else if line.starts_with("@@") {
+                    Color::Cyan
+                } else {
+                    Color::Gray
+                };
+                Line::styled(line.to_string(), Style::default().fg(color))
+            })),
+            Err(e) => lines.push(Line::styled(e.clone(), Style::default().fg(Color::Red))),
+        }
+        lines.push(Line::from(""));
+    }
+    lines
+}
+
+// Result of the last integrity check of the package
+fn format_integrity(package: &Package) -> String {
+    const SHOWN: usize = 20;
+    match &package.integrity {
+        None => String::new(),
+        Some(Err(e)) => format!("\nIntegrity: not checked, cannot read the mtree: {}", e),
+        Some(Ok(report)) if report.is_ok() => {
+            format!("\nIntegrity: OK, {} paths checked", report.checked)
+        }
+        Some(Ok(report)) => {
+            let mut text = format!(
+                "\nIntegrity: {} problem(s) in {} paths",
+                report.problems.len(),
+                report.checked
+            );
+            for problem in report.problems.iter().take(SHOWN) {
+                text.push_str(&format!("\n  /{}: {}", problem.path, problem.problem));
+            }
+            if report.problems.len() > SHOWN {
+                text.push_str(&format!("\n  ... and {} more", report.problems.len() - SHOWN));
+            }
+            text
+        }
+    }
+}
+
+// Backup files of the package that differ from the packaged version
+fn format_modified_configs(app: &App, name: &str) -> String {
+    let files = app.state.modified_configs.for_package(name);
+    if files.is_empty() {
+        return String::new();
+    }
+    let mut text = "\nChanged Configs:".to_string();
+    for file in files {
+        text.push_str(&format!("\n  /{} ({})", file.path, file.status));
+    }
+    text
+}
+
+// Most recent pacman.log events of a package, newest first
+fn format_history(app: &App, package_name: &str) -> String {
+    const SHOWN: usize = 5;
+    let events: Vec<_> = app.state.pacman_log.history(package_name).collect();
+    if events.is_empty() {
+        return String::new();
+    }
+    let mut text = "\nHistory:".to_string();
+    for event in events.iter().rev().take(SHOWN) {
+        text.push_str(&format!(
+            "\n  {} {} {}",
+            event.timestamp.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            event.action,
+            event.versions()
+        ));
+    }
+    if events.len() > SHOWN {
+        text.push_str(&format!("\n  ... {} older event(s)", events.len() - SHOWN));
+    }
+    text
+}
+
+// AUR specific lines of the package information
+fn format_aur_info(aur: &AurPackage, stale_since: Option<DateTime<Utc>>) -> String {
+    let format_date = |timestamp: i64| timestamp_to_date(timestamp).format("%Y-%m-%d").to_string();
+    let stale = match stale_since {
+        Some(date) => format!(" (cached {}, AUR unreachable)", date.format("%Y-%m-%d")),
+        None => String::new(),
+    };
+    let out_of_date = match aur.out_of_date {
+        Some(flagged) => format!("flagged {}", format_date(flagged)),
+        None => "No".to_string(),
+    };
+    format!(
+        "\nAUR: {} votes, popularity {:.2}{}\nAUR Version: {}\nMaintainer: {}\nOut of Date: {}\nSubmitted: {}\nLast Modified: {}\nURL: {}\nKeywords: {}",
+        aur.num_votes,
+        aur.popularity,
+        stale,
+        aur.version,
+        aur.maintainer.as_deref().unwrap_or("None (orphaned)"),
+        out_of_date,
+        format_date(aur.first_submitted),
+        format_date(aur.last_modified),
+        aur.url.as_deref().unwrap_or("None"),
+        join_or_none(&aur.keywords),
+    )
+}
+
+fn render_dependency_tree(frame: &mut Frame, area: Rect, app: &mut App) {
+    // The tree follows the selected package
+    let selected_name = app
+        .selected_package
+        .selected()
+        .and_then(|i| app.state.filtered_packages.get(i))
+        .map(|p| p.name.clone());
+    app.dependency_tree_state
+        .set_root(selected_name.as_deref(), &app.state.dependency_graph);
+
+    let is_focused = matches!(app.input_mode, InputMode::DependencyTree);
+    let title = if app.dependency_tree_state.reverse {
+        "Required By Tree (t, r)"
+    } else {
+        "Dependency Tree (t, r)"
+    };
+
+    let items: Vec<ListItem> = app
+        .dependency_tree_state
+        .rows
+        .iter()
+        .map(|row| {
+            let marker = if !row.has_children {
+                "  "
+            } else if row.expanded {
+                "▾ "
+            } else {
+                "▸ "
+            };
+            let suffix = if row.is_cycle { " (cycle)" } else { "" };
+            ListItem::new(format!("{}{}{}{}", "  ".repeat(row.depth), marker, row.name, suffix))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(if is_focused {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                }),
+        )
+        .highlight_style(if is_focused {
+            Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray)
+        } else {
+            Style::default()
+        })
+        .highlight_symbol(if is_focused { "> " } else { "  " });
+
+    frame.render_stateful_widget(list, area, &mut app.dependency_tree_state.selection);
+}
+
+fn join_or_none(items: &[String]) -> String {
+    if items.is_empty() {
+        "None".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
 fn render_show_mode(frame: &mut Frame, area: Rect, app: &App) {
     let block = Block::default().title("Show Mode (v)").borders(Borders::ALL);
-    let text = format!("Current: {}", app.show_mode_state.active_show_mode);
+    let text = match (&app.show_mode_state.active_show_mode, &app.state.time_travel) {
+        (ShowMode::TimeTravel, Some(time_travel)) => format!(
+            "Current: Time Travel to {}",
+            time_travel.moment.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        ),
+        (show_mode, _) => format!("Current: {}", show_mode),
+    };
     let paragraph = Paragraph::new(text).block(block);
     frame.render_widget(paragraph, area);
 }
@@ -165,13 +691,22 @@ fn render_filters(frame: &mut Frame, area: Rect, app: &App) {
         .map(|(k, _)| k.clone())
         .collect();
 
-    let text = format!(
+    let mut text = format!(
         "Include Tags: {}\nExclude Tags: {}\nInclude Repos: {}\nExclude Repos: {}",
         include_tags.join(", "),
         exclude_tags.join(", "),
         include_repos.join(", "),
         exclude_repos.join(", "),
     );
+    match &app.dependency_filter {
+        Some(DependencyFilter::DependenciesOf(name)) => {
+            text.push_str(&format!("\nDependencies of: {} (Esc)", name))
+        }
+        Some(DependencyFilter::RequiredBy(name)) => {
+            text.push_str(&format!("\nReverse deps of: {} (Esc)", name))
+        }
+        None => {}
+    }
     let paragraph = Paragraph::new(text).block(block);
     frame.render_widget(paragraph, area);
 }
@@ -186,7 +721,10 @@ fn render_sorting(frame: &mut Frame, area: Rect, app: &App) {
 fn render_actions(frame: &mut Frame, area: Rect, app: &App) {
     let block = Block::default().title("Actions (?)").borders(Borders::ALL);
     let text = match app.input_mode {
-        InputMode::Normal => "Actions:\n- (a)dd tag\n- (d)elete tag\n- (?) all actions",
+        InputMode::Normal => "Actions:\n- (a)dd tag\n- (d)elete tag\n- (t) dependency tree\n- (L) recent transactions\n- (T) time travel\n- (O) find file owner\n- (x/X) removal impact\n- (Space) mark, (e/E) as explicit/dep\n- (w) why installed\n- (C) clean package cache\n- (?) all actions",
+        InputMode::DependencyTree => {
+            "j/k: move, l/h: expand/collapse, r: reverse\nEnter: jump to package, Esc: back"
+        }
         InputMode::Tagging => "Enter tag to add, then press Enter",
         InputMode::Untagging => "Enter tag to remove, then press Enter",
         _ => "",
@@ -292,6 +830,511 @@ fn render_show_mode_modal(frame: &mut Frame, app: &mut App) {
     frame.render_stateful_widget(list, area.inner(Margin { horizontal: 1, vertical: 1 }), &mut app.show_mode_state.selection);
 }
 
+fn render_transactions_modal(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(80, 70, frame.area());
+    let block = Block::default()
+        .title("Recent Transactions (Enter: time travel)")
+        .borders(Borders::ALL);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let modal_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(area.inner(Margin { horizontal: 1, vertical: 1 }));
+
+    let log = &app.state.pacman_log;
+    let items: Vec<ListItem> = log
+        .transactions
+        .iter()
+        .rev()
+        .map(|transaction| {
+            ListItem::new(format!(
+                "{}  {} package(s)",
+                transaction.started.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                transaction.events.len()
+            ))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Transactions"))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray))
+        .highlight_symbol("> ");
+
+    let events: Vec<ListItem> = app
+        .transactions_state
+        .selected_transaction(log.transactions.len())
+        .map(|i| {
+            log.transaction_events(&log.transactions[i])
+                .map(|event| {
+                    ListItem::new(format!("{} {} ({})", event.action, event.package, event.versions()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let events_list = List::new(events).block(Block::default().borders(Borders::ALL).title("Changes"));
+
+    frame.render_stateful_widget(list, modal_layout[0], &mut app.transactions_state.selection);
+    frame.render_widget(events_list, modal_layout[1]);
+}
+
+fn render_time_travel_modal(frame: &mut Frame, app: &App) {
+    let area = centered_rect(50, 20, frame.area());
+    let block = Block::default().title("Time Travel").borders(Borders::ALL);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let modal_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(area.inner(Margin { horizontal: 1, vertical: 1 }));
+
+    let input = Paragraph::new(app.time_travel_state.input.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .block(Block::default().borders(Borders::ALL).title("Date (YYYY-MM-DD [HH:MM])"));
+    frame.render_widget(input, modal_layout[0]);
+    frame.render_widget(
+        Paragraph::new("Enter: show the packages installed back then, Esc: cancel"),
+        modal_layout[1],
+    );
+
+    frame.set_cursor_position(Position {
+        x: modal_layout[0].x + app.time_travel_state.input.len() as u16 + 1,
+        y: modal_layout[0].y + 1,
+    });
+}
+
+fn render_owner_search_modal(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 60, frame.area());
+    let block = Block::default()
+        .title("Find File Owner (Enter: jump to package)")
+        .borders(Borders::ALL);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let modal_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(area.inner(Margin { horizontal: 1, vertical: 1 }));
+
+    let input = Paragraph::new(app.owner_search_state.input.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .block(Block::default().borders(Borders::ALL).title("Path or file name"));
+    frame.render_widget(input, modal_layout[0]);
+
+    let items: Vec<ListItem> = if app.state.file_owners.is_none() {
+        vec![ListItem::new("Indexing installed files…")]
+    } else {
+        app.owner_search_state
+            .results
+            .iter()
+            .map(|(path, owner)| ListItem::new(format!("/{} is owned by {}", path, owner)))
+            .collect()
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Owners"))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, modal_layout[1], &mut app.owner_search_state.selection);
+
+    frame.set_cursor_position(Position {
+        x: modal_layout[0].x + app.owner_search_state.input.len() as u16 + 1,
+        y: modal_layout[0].y + 1,
+    });
+}
+
+fn render_removal_preview_modal(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 70, frame.area());
+    let state = &mut app.removal_preview_state;
+    let block = Block::default().title(state.title.as_str()).borders(Borders::ALL);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let modal_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(area.inner(Margin { horizontal: 1, vertical: 1 }));
+
+    let Some(impact) = &state.impact else {
+        let items: Vec<ListItem> = app
+            .state
+            .all_tags
+            .iter()
+            .map(|tag| ListItem::new(tag.as_str()))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Tag"))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, modal_layout[0], &mut state.tag_selection);
+        frame.render_widget(Paragraph::new("Enter: show impact, Esc: cancel"), modal_layout[1]);
+        return;
+    };
+
+    let text = format_removal_impact(impact, &app.state.packages);
+    let max_scroll = text.lines.len().saturating_sub(1) as u16;
+    let paragraph = Paragraph::new(text).scroll((state.scroll.min(max_scroll), 0));
+    frame.render_widget(paragraph, modal_layout[0]);
+
+    let hint = match &state.command {
+        Some(command) => format!("Enter/y: run `{}`, Esc/n: cancel, j/k: scroll", command.join(" ")),
+        None => "Esc: close, j/k: scroll".to_string(),
+    };
+    frame.render_widget(Paragraph::new(hint), modal_layout[1]);
+}
+
+fn format_removal_impact(impact: &RemovalImpact, packages: &[Package]) -> Text<'static> {
+    if impact.targets.is_empty() {
+        return Text::from("Nothing installed would be removed.");
+    }
+
+    let size_of = |name: &str| {
+        packages
+            .iter()
+            .find(|p| p.name == name)
+            .map_or(0.0, |p| p.size)
+    };
+    let heading = |text: String| Line::styled(text, Style::default().add_modifier(Modifier::BOLD));
+
+    let mut lines = vec![
+        Line::from(format!(
+            "Removes {} package(s), freeing {:.2} MiB",
+            impact.removed_count(),
+            impact.freed_mib
+        )),
+        Line::from(""),
+        heading(format!("Targets ({}):", impact.targets.len())),
+    ];
+    for name in &impact.targets {
+        lines.push(Line::from(format!("  {} ({:.2} MiB)", name, size_of(name))));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(heading(format!(
+        "Unneeded dependencies ({}):",
+        impact.dependencies.len()
+    )));
+    for name in &impact.dependencies {
+        lines.push(Line::from(format!("  {} ({:.2} MiB)", name, size_of(name))));
+    }
+
+    if !impact.is_safe() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            format!(
+                "Would break {} package(s); pacman will refuse the removal:",
+                impact.broken.len()
+            ),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+        for (name, needs) in &impact.broken {
+            lines.push(Line::styled(
+                format!("  {} requires {}", name, needs.join(", ")),
+                Style::default().fg(Color::Red),
+            ));
+        }
+    }
+
+    if !impact.optional_lost.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(heading(format!(
+            "Optional dependencies lost ({}):",
+            impact.optional_lost.len()
+        )));
+        for (name, lost) in &impact.optional_lost {
+            lines.push(Line::styled(
+                format!("  {} loses {}", name, lost.join(", ")),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+    }
+    Text::from(lines)
+}
+
+fn render_downgrade_modal(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 60, frame.area());
+    let state = &mut app.downgrade_state;
+    let block = Block::default()
+        .title(format!("Cached Versions of {}", state.package))
+        .borders(Borders::ALL);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let modal_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(area.inner(Margin { horizontal: 1, vertical: 1 }));
+
+    let Some(preview) = &state.preview else {
+        let items: Vec<ListItem> = state
+            .versions
+            .iter()
+            .map(|cached| {
+                let built = match &cached.desc {
+                    Ok(desc) => desc.build_date.map_or("unknown".to_string(), |date| {
+                        timestamp_to_date(date).format("%Y-%m-%d").to_string()
+                    }),
+                    Err(_) => "unreadable".to_string(),
+                };
+                let mut line = format!(
+                    "{:<20} built {:<10}  {}",
+                    cached.file.version,
+                    built,
+                    format_size(cached.file.size)
+                );
+                if state.installed_version.as_deref() == Some(cached.file.version.as_str()) {
+                    line.push_str("  (installed)");
+                }
+                ListItem::new(line)
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Version"))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, modal_layout[0], &mut state.selection);
+        frame.render_widget(Paragraph::new("Enter: preview install, Esc: cancel"), modal_layout[1]);
+        return;
+    };
+
+    let text = format_install_preview(preview);
+    let max_scroll = text.lines.len().saturating_sub(1) as u16;
+    let paragraph = Paragraph::new(text).scroll((state.scroll.min(max_scroll), 0));
+    frame.render_widget(paragraph, modal_layout[0]);
+
+    let hint = match state.selected_version() {
+        Some(version) => format!(
+            "Enter/y: install {}, Esc/n: back, j/k: scroll",
+            version.file.path.display()
+        ),
+        None => "Esc/n: back".to_string(),
+    };
+    frame.render_widget(Paragraph::new(hint), modal_layout[1]);
+}
+
+fn format_install_preview(preview: &InstallPreview) -> Text<'static> {
+    let heading = |text: String| Line::styled(text, Style::default().add_modifier(Modifier::BOLD));
+
+    let change = match (&preview.from, preview.direction()) {
+        (Some(from), Some(Ordering::Less)) => format!("Downgrades {} {} -> {}", preview.name, from, preview.to),
+        (Some(from), Some(Ordering::Greater)) => format!("Upgrades {} {} -> {}", preview.name, from, preview.to),
+        (Some(_), _) => format!("Reinstalls {} {}", preview.name, preview.to),
+        (None, _) => format!("Installs {} {}", preview.name, preview.to),
+    };
+    let mut lines = vec![heading(change), Line::from("")];
+
+    if preview.is_safe() {
+        lines.push(Line::styled(
+            "All dependencies stay satisfied.",
+            Style::default().fg(Color::Green),
+        ));
+    }
+
+    if !preview.broken_dependents.is_empty() {
+        lines.push(heading(format!(
+            "Installed packages left unsatisfied ({}):",
+            preview.broken_dependents.len()
+        )));
+        for (name, depends) in &preview.broken_dependents {
+            lines.push(Line::styled(
+                format!("  {} requires {}", name, depends.join(", ")),
+                Style::default().fg(Color::Red),
+            ));
+        }
+    }
+
+    if !preview.unmet_depends.is_empty() {
+        if !preview.broken_dependents.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(heading(format!(
+            "Dependencies not satisfied by installed packages ({}):",
+            preview.unmet_depends.len()
+        )));
+        for (depend, installed) in &preview.unmet_depends {
+            let line = match installed {
+                Some(version) => format!("  {} (installed: {})", depend, version),
+                None => format!("  {} (not installed)", depend),
+            };
+            lines.push(Line::styled(line, Style::default().fg(Color::Yellow)));
+        }
+    }
+    Text::from(lines)
+}
+
+fn render_config_drift_modal(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 40, frame.area());
+    let block = Block::default().title("Resolve Config Drift").borders(Borders::ALL);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let modal_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(2)].as_ref())
+        .split(area.inner(Margin { horizontal: 1, vertical: 1 }));
+
+    let state = &mut app.config_drift_state;
+    let items: Vec<ListItem> = state
+        .files
+        .iter()
+        .map(|file| ListItem::new(format!("/{}", file.drift_path())))
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("File"))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, modal_layout[0], &mut state.selection);
+
+    let kind = state
+        .selection
+        .selected()
+        .and_then(|i| state.files.get(i))
+        .map(|file| file.kind);
+    if let Some(question) = state.confirmation() {
+        frame.render_widget(
+            Paragraph::new(question).style(Style::default().fg(Color::Yellow)),
+            modal_layout[1],
+        );
+        return;
+    }
+    let hint = match kind {
+        Some(DriftKind::Pacsave(_)) => "o: restore your saved version, n: keep the current file",
+        _ => "o: keep your version, n: take the package's version",
+    };
+    frame.render_widget(
+        Paragraph::new(format!(
+            "{}\nm: merge with `{}`, Esc: cancel",
+            hint,
+            app.config.pacman.merge_tool.join(" ")
+        )),
+        modal_layout[1],
+    );
+}
+
+fn render_unowned_modal(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 70, frame.area());
+    let state = &mut app.unowned_state;
+    let title = if state.scanning {
+        "Unowned Files".to_string()
+    } else {
+        format!(
+            "Unowned Files ({} path(s), {})",
+            state.files.path_count(),
+            format_size(state.files.total_size())
+        )
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let modal_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(area.inner(Margin { horizontal: 1, vertical: 1 }));
+
+    let mut items: Vec<ListItem> = Vec::new();
+    for dir in state.files.dirs() {
+        items.push(
+            ListItem::new(format!(
+                "/{} ({} path(s), {})",
+                dir.path,
+                dir.entries.len(),
+                format_size(dir.size)
+            ))
+            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        );
+        items.extend(dir.entries.iter().map(|entry| {
+            let style = if entry.is_dir() {
+                Style::default().fg(Color::Blue)
+            } else {
+                Style::default()
+            };
+            ListItem::new(format!("  {} ({})", entry.name(), format_size(entry.size))).style(style)
+        }));
+    }
+    let list = if state.scanning {
+        List::new(vec![ListItem::new("Scanning…")])
+    } else if items.is_empty() {
+        List::new(vec![ListItem::new("No unowned files found.")])
+    } else {
+        List::new(items)
+    };
+    let list = list
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Not owned by any package under {}",
+            app.config
+                .unowned
+                .roots
+                .iter()
+                .map(|p| p.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, modal_layout[0], &mut state.selection);
+
+    frame.render_widget(
+        Paragraph::new("Largest directories first. j/k: scroll, Esc: close"),
+        modal_layout[1],
+    );
+}
+
+fn render_cache_cleanup_modal(frame: &mut Frame, app: &App) {
+    let area = centered_rect(70, 60, frame.area());
+    let block = Block::default().title("Clean Package Cache").borders(Borders::ALL);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let modal_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(area.inner(Margin { horizontal: 1, vertical: 1 }));
+
+    let state = &app.cache_cleanup_state;
+    let input = Paragraph::new(state.input.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .block(Block::default().borders(Borders::ALL).title("Versions to keep per package"));
+    frame.render_widget(input, modal_layout[0]);
+
+    let items: Vec<ListItem> = state
+        .candidates
+        .iter()
+        .map(|file| {
+            let name = file.path.file_name().unwrap_or_default().to_string_lossy();
+            ListItem::new(format!("{} ({})", name, format_size(file.size)))
+        })
+        .collect();
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(
+                "{} file(s) to delete, freeing {}",
+                state.candidates.len(),
+                format_size(state.freed())
+            )),
+    );
+    frame.render_widget(list, modal_layout[1]);
+    frame.render_widget(
+        Paragraph::new("Enter: delete these files, Esc: cancel. Installed versions are always kept."),
+        modal_layout[2],
+    );
+
+    frame.set_cursor_position(Position {
+        x: modal_layout[0].x + state.input.len() as u16 + 1,
+        y: modal_layout[0].y + 1,
+    });
+}
+
 fn render_sort_modal(frame: &mut Frame, app: &mut App) {
     let area = centered_rect(60, 50, frame.area());
     let block = Block::default().title("Sort by").borders(Borders::ALL);